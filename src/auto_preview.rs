@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::hash;
+use crate::pipeline::{self, PipelineOutput};
+
+/// Periodically renders a small H.264 preview of the timeline into a folder (typically one a
+/// sync client shares), so reviewers always have the latest cut. A render only starts when the
+/// timeline changed since the last one, and is written under a temporary name then renamed.
+pub struct AutoPreview {
+    last_check: Instant,
+    rendered: Option<String>, // hash of the render arguments last written
+    running: Option<(String, mpsc::Receiver<bool>)>,
+}
+
+impl AutoPreview {
+    pub fn new() -> Self {
+        Self { last_check: Instant::now(), rendered: None, running: None }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Collects a finished render; returns its outcome once.
+    pub fn poll(&mut self) -> Option<bool> {
+        let (key, receiver) = self.running.as_ref()?;
+        let success = receiver.try_recv().ok()?;
+        if success {
+            self.rendered = Some(key.clone());
+        }
+        self.running = None;
+        Some(success)
+    }
+
+    /// Time until the next check is due, or None when one is due now.
+    pub fn due_in(&self, interval: Duration) -> Option<Duration> {
+        interval.checked_sub(self.last_check.elapsed()).filter(|d| !d.is_zero())
+    }
+
+    /// Starts a render of `timeline_args` (a full-size render ending in a placeholder output)
+    /// to `<dir>/<name>.preview.mp4` unless the same timeline was already rendered.
+    pub fn start(&mut self, ctx: &egui::Context, timeline_args: &[String], dir: &Path, name: &str) -> Result<bool, &'static str> {
+        self.last_check = Instant::now();
+        let key = hash::sha256_hex(timeline_args.join("\n").as_bytes());
+        if self.running.is_some() || self.rendered.as_ref() == Some(&key) {
+            return Ok(false);
+        }
+
+        let output = PipelineOutput {
+            suffix: "preview.partial".to_string(),
+            height: Some(360),
+            options: "-c:v libx264 -preset veryfast -crf 30 -pix_fmt yuv420p -c:a aac -b:a 96k -movflags +faststart"
+                .split(' ').map(String::from).collect(),
+            ..Default::default()
+        };
+        let base = dir.join(format!("{}.mp4", name));
+        let partial = output.path(&base);
+        let args = pipeline::build_args(timeline_args, std::slice::from_ref(&output), &base)?;
+        let finished = dir.join(format!("{}.preview.mp4", name));
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let success = Command::new("ffmpeg")
+                .args(&args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success())
+                && std::fs::rename(&partial, &finished).is_ok();
+            if !success {
+                let _ = std::fs::remove_file(&partial);
+            }
+            let _ = sender.send(success);
+            ctx.request_repaint();
+        });
+        self.running = Some((key, receiver));
+        Ok(true)
+    }
+}
+
+/// Name for the preview file: the project's file name, or "untitled".
+pub fn preview_name(project_path: Option<&PathBuf>) -> String {
+    project_path
+        .and_then(|p| p.file_stem())
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "untitled".to_string())
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hash;
+
+// Camera cards keep clips in fixed folder layouts; everything else on the card
+// (thumbnails, XML sidecars, playlists) is skipped.
+const LAYOUTS: &[(&str, &str, &[&str])] = &[
+    // (label, folder relative to the card root, clip extensions)
+    ("AVCHD", "PRIVATE/AVCHD/BDMV/STREAM", &["mts"]),
+    ("XAVC", "PRIVATE/M4ROOT/CLIP", &["mp4"]),
+    ("XDCAM", "XDROOT/Clip", &["mxf"]),
+    ("XDCAM", "Clip", &["mxf"]),
+    ("XDCAM EX", "BPAV/CLPR", &["mp4"]),
+    ("DCIM", "DCIM", &["mp4", "mov", "mts", "avi"]),
+];
+
+pub struct CardClip {
+    pub path: PathBuf,
+    pub layout: &'static str,
+    pub size: u64,
+}
+
+/// Finds clips under the known camera layouts at `root`. DCIM and XDCAM EX keep clips
+/// one folder deeper (DCIM/100GOPRO, BPAV/CLPR/<clip>), so subfolders are searched too.
+pub fn scan(root: &Path) -> Vec<CardClip> {
+    let mut clips = Vec::new();
+    for (layout, folder, extensions) in LAYOUTS {
+        let dir = root.join(folder);
+        if !dir.is_dir() {
+            continue;
+        }
+        collect(&dir, layout, extensions, 2, &mut clips);
+    }
+    clips.sort_by(|a, b| a.path.cmp(&b.path));
+    clips.dedup_by(|a, b| a.path == b.path);
+    clips
+}
+
+fn collect(dir: &Path, layout: &'static str, extensions: &[&str], depth: u32, clips: &mut Vec<CardClip>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.is_dir() {
+            if depth > 0 {
+                collect(&path, layout, extensions, depth - 1, clips);
+            }
+            continue;
+        }
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        // macOS leaves "._" resource forks on FAT cards
+        let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with("._"));
+        if extensions.contains(&extension.as_str()) && !hidden {
+            clips.push(CardClip { path, layout, size: meta.len() });
+        }
+    }
+}
+
+/// Copies a card clip into `media_dir`, verifying the copy by hash and recording it in the
+/// folder's checksum ledger. Camera file names repeat across cards (C0001.MP4), so an
+/// existing file with different contents gets a numbered name instead of being overwritten.
+pub fn copy_to_media(clip: &CardClip, media_dir: &Path) -> Result<PathBuf, &'static str> {
+    fs::create_dir_all(media_dir).map_err(|_| "Error creating media folder")?;
+    let file_name = clip.path.file_name().ok_or("Card clip has no file name")?;
+    let mut target = media_dir.join(file_name);
+    let mut n = 1;
+    while let Ok(existing) = fs::metadata(&target) {
+        if existing.len() == clip.size
+            && let (Ok(source_hash), Ok(existing_hash)) = (hash::sha256_file(&clip.path), hash::sha256_file(&target))
+            && source_hash == existing_hash
+        {
+            let _ = hash::record_checksum(&target, &existing_hash);
+            return Ok(target); // already copied
+        }
+        let stem = clip.path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = clip.path.extension().unwrap_or_default().to_string_lossy();
+        target = media_dir.join(format!("{}_{}.{}", stem, n, extension));
+        n += 1;
+    }
+    let sha256 = hash::verified_copy(&clip.path, &target)?;
+    hash::record_checksum(&target, &sha256).map_err(|_| "Copied, but writing the checksum ledger failed")?;
+    Ok(target)
+}
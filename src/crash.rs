@@ -0,0 +1,109 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{diagnostics, hash, settings, VideoClip};
+
+// Crash reports go to <config dir>/crashes/crash-<unix time>.txt. A report is offered once
+// on the next launch, then renamed to crash-<unix time>.seen.txt.
+const MAX_ACTIONS: usize = 30;
+
+struct State {
+    actions: VecDeque<String>,
+    timeline: String,
+}
+
+static STATE: Mutex<State> = Mutex::new(State { actions: VecDeque::new(), timeline: String::new() });
+
+/// Chains a panic hook that writes a report before the default hook prints the panic.
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = write_report(&info.to_string());
+        default_hook(info);
+    }));
+}
+
+/// Logs a user-visible action (every status message) and the timeline it left behind.
+/// Paths are replaced by short hashes so reports can be shared.
+pub fn record(action: &str, clips: &[VideoClip]) {
+    let Ok(mut state) = STATE.lock() else { return };
+    if !action.is_empty() {
+        if state.actions.len() == MAX_ACTIONS {
+            state.actions.pop_front();
+        }
+        state.actions.push_back(anonymize(action));
+    }
+    state.timeline = clips.iter()
+        .map(|c| format!(
+            "  {} start={} trim={}..{} of {}{}\n",
+            if c.generator.is_some() { "generator".to_string() } else { path_token(&c.path.to_string_lossy()) },
+            c.timeline_start, c.trim_start, c.trim_end, c.duration,
+            if c.trim_end > c.duration || c.trim_start >= c.trim_end { " INVALID" } else { "" }
+        ))
+        .collect();
+}
+
+fn write_report(panic: &str) -> std::io::Result<()> {
+    let dir = crashes_dir().ok_or(std::io::ErrorKind::NotFound)?;
+    fs::create_dir_all(&dir)?;
+    let created = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    let mut report = format!(
+        "videoedit {} ({} {}) crashed at unix time {}\n\n{}\n\n",
+        env!("CARGO_PKG_VERSION"), std::env::consts::OS, std::env::consts::ARCH, created, anonymize(panic)
+    );
+    for tool in ["ffmpeg", "ffprobe"] {
+        report.push_str(&format!("{}: {}\n", tool, diagnostics::tool_version(tool)));
+    }
+    // the panicking thread may have held the lock; a poisoned state is still readable
+    let state = STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    report.push_str("\nLast actions (oldest first):\n");
+    for action in &state.actions {
+        report.push_str(&format!("  {}\n", action));
+    }
+    report.push_str(&format!("\nTimeline:\n{}", state.timeline));
+    report.push_str(&format!("\nBacktrace:\n{}\n", std::backtrace::Backtrace::force_capture()));
+
+    fs::write(dir.join(format!("crash-{}.txt", created)), report)
+}
+
+fn crashes_dir() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join("crashes"))
+}
+
+/// The newest report not yet offered to the user.
+pub fn pending_report() -> Option<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(crashes_dir()?).ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("crash-") && name.ends_with(".txt") && !name.ends_with(".seen.txt")
+        })
+        .collect();
+    reports.sort();
+    reports.pop()
+}
+
+/// Marks a report as offered, returning where it now lives.
+pub fn acknowledge(report: &PathBuf) -> PathBuf {
+    let seen = report.with_extension("seen.txt");
+    match fs::rename(report, &seen) {
+        Ok(()) => seen,
+        Err(_) => report.clone(),
+    }
+}
+
+/// Replaces anything that looks like a file path with a stable short hash.
+fn anonymize(text: &str) -> String {
+    text.split(' ')
+        .map(|word| if word.contains('/') || word.contains('\\') { path_token(word) } else { word.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn path_token(path: &str) -> String {
+    format!("<path {}>", &hash::sha256_hex(path.as_bytes())[..8])
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+use std::process::Command;
+
+// filters the export graph depends on
+// every filter the timeline, stems, pipelines, outro and analysis graphs build
+const REQUIRED_FILTERS: &[&str] = &[
+    "scale", "pad", "setsar", "setdar", "concat", "fps", "format", "trim", "tpad", "crop", "cropdetect",
+    "transpose", "hflip", "vflip", "deshake", "lutrgb", "drawtext", "overlay", "xfade", "split", "color",
+    "volume", "afade", "adelay", "apad", "atrim", "asetpts", "aformat", "asplit", "anullsrc",
+];
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// First line of `<tool> -version`, e.g. "ffmpeg version 6.1.1 ...".
+pub fn tool_version(tool: &str) -> String {
+    run(tool, &["-version"])
+        .and_then(|out| out.lines().next().map(str::to_string))
+        .unwrap_or_else(|| "NOT FOUND".to_string())
+}
+
+/// Second column of ffmpeg's `-encoders`/`-filters` tables (after the flags column).
+fn listed_names(table: &str) -> Vec<String> {
+    table.lines()
+        .filter_map(|l| {
+            let mut cols = l.split_whitespace();
+            let flags = cols.next()?;
+            let name = cols.next()?;
+            // legend rows look like "V..... = Video"
+            (name != "=" && flags.chars().all(|c| c == '.' || c == '|' || c.is_ascii_uppercase()))
+                .then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Windows has no `df`, so the report says the check was skipped rather than "unknown".
+fn free_space(dir: &Path) -> Option<String> {
+    if cfg!(windows) {
+        return Some("free space not checked on Windows".to_string());
+    }
+    let out = run("df", &["-Pk", &dir.to_string_lossy()])?;
+    let available_kb: u64 = out.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(format!("{} MB free", available_kb / 1024))
+}
+
+/// Runs the capability probes and returns a plain-text report suitable for pasting into a bug report.
+pub fn capability_report(cache_dir: &Path) -> String {
+    let mut report = format!(
+        "videoedit {} ({} {})\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+
+    for tool in ["ffmpeg", "ffprobe"] {
+        report.push_str(&format!("{}: {}\n", tool, tool_version(tool)));
+    }
+
+    match run("ffmpeg", &["-hide_banner", "-hwaccels"]) {
+        Some(out) => {
+            let hwaccels: Vec<&str> = out.lines().skip(1).map(str::trim).filter(|l| !l.is_empty()).collect();
+            report.push_str(&format!("hwaccels: {}\n", if hwaccels.is_empty() { "none".to_string() } else { hwaccels.join(", ") }));
+        }
+        None => report.push_str("hwaccels: unavailable\n"),
+    }
+
+    match run("ffmpeg", &["-hide_banner", "-encoders"]) {
+        Some(out) => {
+            let encoders = listed_names(&out);
+            let notable: Vec<&str> = encoders.iter()
+                .map(String::as_str)
+                .filter(|e| ["264", "265", "hevc", "aac", "prores", "vp9", "av1"].iter().any(|k| e.contains(k)))
+                .collect();
+            report.push_str(&format!("encoders: {} total; {}\n", encoders.len(), notable.join(", ")));
+        }
+        None => report.push_str("encoders: unavailable\n"),
+    }
+
+    match run("ffmpeg", &["-hide_banner", "-filters"]) {
+        Some(out) => {
+            let filters = listed_names(&out);
+            let missing: Vec<&str> = REQUIRED_FILTERS.iter()
+                .copied()
+                .filter(|f| !filters.iter().any(|name| name == f))
+                .collect();
+            report.push_str(&format!(
+                "filters: {} total; required {}\n",
+                filters.len(),
+                if missing.is_empty() { "all present".to_string() } else { format!("MISSING {}", missing.join(", ")) }
+            ));
+        }
+        None => report.push_str("filters: unavailable\n"),
+    }
+
+    report.push_str(&format!(
+        "cache dir: {} ({})\n",
+        cache_dir.display(),
+        free_space(cache_dir).unwrap_or_else(|| "free space unknown".to_string())
+    ));
+
+    report
+}
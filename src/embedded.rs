@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+// Chapters and subtitle streams carried inside source containers (mostly MKV),
+// read with ffprobe's `flat` writer:
+//   chapters.chapter.0.start_time="12.500000"
+//   chapters.chapter.0.tags.title="Intro"
+//   streams.stream.2.index=3
+//   streams.stream.2.codec_type="subtitle"
+
+/// Bitmap subtitle codecs cannot be converted to SRT text.
+const BITMAP_SUBTITLE_CODECS: &[&str] = &["hdmv_pgs_subtitle", "dvd_subtitle", "dvb_subtitle", "xsub"];
+
+#[derive(Clone)]
+pub struct Chapter {
+    pub start_ms: u32, // into the source file
+    pub title: String,
+}
+
+#[derive(Clone)]
+pub struct SubtitleStream {
+    pub index: u32, // container stream index, for `-map 0:<index>`
+    pub codec: String,
+    pub language: Option<String>,
+}
+
+impl SubtitleStream {
+    pub fn is_text(&self) -> bool {
+        !BITMAP_SUBTITLE_CODECS.contains(&self.codec.as_str())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct EmbeddedData {
+    pub chapters: Vec<Chapter>,
+    pub subtitles: Vec<SubtitleStream>,
+}
+
+impl EmbeddedData {
+    pub fn is_empty(&self) -> bool {
+        self.chapters.is_empty() && self.subtitles.is_empty()
+    }
+}
+
+pub fn probe(path: &Path) -> Result<EmbeddedData, &'static str> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_chapters"])
+        .args(["-show_entries", "stream=index,codec_name,codec_type:stream_tags=language"])
+        .args(["-of", "flat"])
+        .arg(path)
+        .output()
+        .map_err(|_| "Error running ffprobe")?;
+    if !output.status.success() {
+        return Err("ffprobe could not read the file");
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // fields keyed by the array position in the flat key
+    let mut chapters: Vec<(Option<u32>, String)> = Vec::new();
+    let mut streams: Vec<(Option<u32>, String, String, Option<String>)> = Vec::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = unquote(value);
+        let mut parts = key.splitn(4, '.');
+        let (Some(section), Some(_), Some(pos)) = (parts.next(), parts.next(), parts.next()) else { continue };
+        let Ok(pos) = pos.parse::<usize>() else { continue };
+        let field = parts.next().unwrap_or("");
+
+        match section {
+            "chapters" => {
+                if chapters.len() <= pos {
+                    chapters.resize(pos + 1, (None, String::new()));
+                }
+                match field {
+                    "start_time" => chapters[pos].0 = value.parse::<f64>().ok().map(|s| (s * 1000.0).round() as u32),
+                    "tags.title" => chapters[pos].1 = value,
+                    _ => {}
+                }
+            }
+            "streams" => {
+                if streams.len() <= pos {
+                    streams.resize(pos + 1, (None, String::new(), String::new(), None));
+                }
+                match field {
+                    "index" => streams[pos].0 = value.parse().ok(),
+                    "codec_name" => streams[pos].1 = value,
+                    "codec_type" => streams[pos].2 = value,
+                    "tags.language" => streams[pos].3 = Some(value),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(EmbeddedData {
+        chapters: chapters.into_iter()
+            .filter_map(|(start_ms, title)| Some(Chapter { start_ms: start_ms?, title }))
+            .collect(),
+        subtitles: streams.into_iter()
+            .filter(|(_, _, kind, _)| kind == "subtitle")
+            .filter_map(|(index, codec, _, language)| Some(SubtitleStream { index: index?, codec, language }))
+            .collect(),
+    })
+}
+
+/// Writes one text subtitle stream out as SRT.
+#[derive(Default)]
+pub struct Extracted {
+    pub files: usize,
+    pub failed: usize,
+    pub error: Option<&'static str>, // the first failure
+}
+
+/// Extracts each text stream to `<source>.<language>.<index>.srt` on a worker, carrying on
+/// past failures. The outcome arrives once on the returned channel.
+pub fn start_extract(ctx: &egui::Context, source: PathBuf, streams: Vec<SubtitleStream>) -> mpsc::Receiver<Extracted> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let mut extracted = Extracted::default();
+        for stream in streams.iter().filter(|s| s.is_text()) {
+            let suffix = match &stream.language {
+                Some(language) => format!("{}.{}.srt", language, stream.index),
+                None => format!("{}.srt", stream.index),
+            };
+            match extract_subtitle(&source, stream, &source.with_extension(suffix)) {
+                Ok(()) => extracted.files += 1,
+                Err(err) => {
+                    extracted.error.get_or_insert(err);
+                    extracted.failed += 1;
+                }
+            }
+        }
+        let _ = sender.send(extracted);
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+fn extract_subtitle(source: &Path, stream: &SubtitleStream, output: &Path) -> Result<(), &'static str> {
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i").arg(source)
+        .arg("-map").arg(format!("0:{}", stream.index))
+        .arg("-c:s").arg("srt")
+        .arg(output)
+        .status()
+        .map_err(|_| "Error running ffmpeg")?;
+    if !status.success() {
+        return Err("Error extracting subtitle stream");
+    }
+    Ok(())
+}
+
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
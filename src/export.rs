@@ -0,0 +1,113 @@
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::{manifest, write_poster, VideoClip};
+
+pub enum ExportEvent {
+    Status(String),
+    Progress(f32), // 0..1 of the main render
+    Finished(bool), // whether the main render succeeded; the last status tells the rest
+}
+
+/// Everything a local render writes, worked out when it starts so the timeline can be
+/// edited while it runs.
+#[derive(Default)]
+pub struct ExportJob {
+    pub output: PathBuf, // the file named in the completion notice
+    pub args: Vec<String>, // the main render, output last
+    pub duration_ms: u32,
+    pub verify: Option<(manifest::Manifest, PathBuf)>, // re-render: sources are checked first and `args` taken from the manifest
+    pub stems: Vec<(&'static str, Vec<String>)>, // stem label, render arguments
+    pub manifest: Option<(Vec<PathBuf>, Vec<VideoClip>)>, // sources and clips to record
+    pub chapters: Option<String>,
+    pub poster_ms: Option<u32>,
+    pub done: String, // status once the main render succeeded
+    pub failed: String,
+}
+
+/// Runs the render on a worker, streaming ffmpeg's progress, then writes the extras
+/// (stems, manifest, chapters, poster) once the main render succeeded.
+pub fn start(job: ExportJob, ctx: egui::Context) -> mpsc::Receiver<ExportEvent> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let send = |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        };
+        let mut args = job.args.clone();
+        if let Some((manifest, manifest_path)) = &job.verify {
+            send(ExportEvent::Status("Verifying manifest sources ...".to_string()));
+            match manifest::verified_args(manifest, manifest_path) {
+                Ok(verified) => args = verified,
+                Err(err) => {
+                    send(ExportEvent::Status(err));
+                    return send(ExportEvent::Finished(false));
+                }
+            }
+            // the output path is always the final argument
+            if let Some(last) = args.last_mut() {
+                *last = job.output.to_string_lossy().into_owned();
+            }
+            send(ExportEvent::Status("Re-rendering from manifest ...".to_string()));
+        }
+
+        let success = render(&args, job.duration_ms, |fraction| send(ExportEvent::Progress(fraction)));
+        if success {
+            send(ExportEvent::Status(job.done.clone()));
+            write_extras(&job, |status| send(ExportEvent::Status(status)));
+        } else {
+            send(ExportEvent::Status(job.failed.clone()));
+        }
+        send(ExportEvent::Finished(success));
+    });
+    receiver
+}
+
+fn render(args: &[String], duration_ms: u32, progress: impl Fn(f32)) -> bool {
+    let Ok(mut child) = Command::new("ffmpeg")
+        .args(["-nostats", "-progress", "pipe:1"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    // -progress writes key=value blocks; out_time_us is the position in the output
+    for line in BufReader::new(child.stdout.take().unwrap()).lines().map_while(Result::ok) {
+        if let Some(us) = line.strip_prefix("out_time_us=").and_then(|v| v.parse::<u64>().ok()) {
+            progress((us as f32 / 1000.0 / duration_ms.max(1) as f32).min(1.0));
+        }
+    }
+    child.wait().is_ok_and(|s| s.success())
+}
+
+fn write_extras(job: &ExportJob, status: impl Fn(String)) {
+    if !job.stems.is_empty() {
+        let failed = job.stems.iter().find(|(label, args)| {
+            status(format!("Exporting {} stem ...", label));
+            !Command::new("ffmpeg").args(args).status().is_ok_and(|s| s.success())
+        });
+        match failed {
+            Some((label, _)) => status(format!("Exported, but the {} stem failed", label)),
+            None => status(job.done.clone()),
+        }
+    }
+    if let Some((sources, clips)) = &job.manifest
+        && let Err(err) = manifest::write(&job.output.with_extension("manifest.json"), &job.output, &job.args, sources, clips)
+    {
+        status(err.to_string());
+    }
+    if let Some(text) = &job.chapters
+        && std::fs::write(job.output.with_extension("chapters.txt"), text).is_err()
+    {
+        status("Exported, but writing the chapters file failed".to_string());
+    }
+    if let Some(poster_ms) = job.poster_ms
+        && let Err(err) = write_poster(&job.output, poster_ms)
+    {
+        status(err.to_string());
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::VideoClip;
+
+const SAMPLES_PER_CLIP: u32 = 3;
+const FRAMES_PER_SAMPLE: u32 = 12;
+
+/// Pixel crop in source coordinates, as taken by ffmpeg's `crop=w:h:x:y`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Crop {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Crop {
+    pub fn filter(&self) -> String {
+        format!("crop={}:{}:{}:{},", self.width, self.height, self.x, self.y)
+    }
+}
+
+/// A "Detect Black Bars" running in the background; the clip is found again by its source
+/// range when the result arrives.
+pub struct CropDetection {
+    pub target_path: PathBuf,
+    pub target_trim: (u32, u32),
+    receiver: mpsc::Receiver<Result<Option<Crop>, &'static str>>,
+}
+
+impl CropDetection {
+    pub fn poll(&self) -> Option<Result<Option<Crop>, &'static str>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+pub fn start(ctx: &egui::Context, clip: &VideoClip) -> CropDetection {
+    let (sender, receiver) = mpsc::channel();
+    let job = clip.clone();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let _ = sender.send(detect(&job));
+        ctx.request_repaint();
+    });
+    CropDetection { target_path: clip.path.clone(), target_trim: (clip.trim_start, clip.trim_end), receiver }
+}
+
+/// Runs cropdetect at a few points across the trimmed clip. The boxes are merged so a dark
+/// shot in one sample cannot crop picture that is visible elsewhere.
+/// Returns None when there are no bars to remove.
+fn detect(clip: &VideoClip) -> Result<Option<Crop>, &'static str> {
+    let clip_duration = clip.trim_end - clip.trim_start;
+    let mut merged: Option<(u32, u32, u32, u32)> = None; // left, top, right, bottom
+
+    for i in 0..SAMPLES_PER_CLIP {
+        let source_ms = clip.trim_start + clip_duration * (2 * i + 1) / (2 * SAMPLES_PER_CLIP);
+        let output = Command::new("ffmpeg")
+            .arg("-ss").arg(format!("{:.3}", source_ms as f32 / 1000.0))
+            .arg("-i").arg(&clip.path)
+            .arg("-frames:v").arg(FRAMES_PER_SAMPLE.to_string())
+            .arg("-vf").arg("cropdetect=limit=24:round=2:reset=0")
+            .arg("-an")
+            .arg("-f").arg("null")
+            .arg("-")
+            .output()
+            .map_err(|_| "Error running ffmpeg")?;
+
+        // cropdetect logs one line per frame ending in "crop=w:h:x:y"; the last is the settled one
+        let log = String::from_utf8_lossy(&output.stderr);
+        let Some(crop) = log.lines().rev().find_map(|line| parse_crop(line.rsplit_once("crop=")?.1)) else {
+            continue;
+        };
+        let (left, top, right, bottom) = merged.unwrap_or((u32::MAX, u32::MAX, 0, 0));
+        merged = Some((left.min(crop.x), top.min(crop.y), right.max(crop.x + crop.width), bottom.max(crop.y + crop.height)));
+    }
+
+    let (left, top, right, bottom) = merged.ok_or("Could not analyse the clip for black bars")?;
+    // bars are symmetric in practice; a box touching the top-left corner means nothing to remove
+    if left == 0 && top == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Crop { width: right - left, height: bottom - top, x: left, y: top }))
+}
+
+fn parse_crop(text: &str) -> Option<Crop> {
+    let mut fields = text.trim().split(':').map(|f| f.parse::<u32>().ok());
+    Some(Crop {
+        width: fields.next()??,
+        height: fields.next()??,
+        x: fields.next()??,
+        y: fields.next()??,
+    })
+}
@@ -0,0 +1,120 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use crate::VideoClip;
+
+const LOOP_WIDTH: u32 = 192;
+const LOOP_HEIGHT: u32 = 108;
+const LOOP_FPS: u32 = 10;
+const MAX_LOOP_FRAMES: usize = 150; // 15 s; longer ranges are sampled evenly to fit
+
+/// What a rendered loop was made from; a change re-renders it.
+#[derive(Clone, PartialEq)]
+struct LoopKey {
+    path: PathBuf,
+    trim_start: u32,
+    trim_end: u32,
+    filters: String,
+}
+
+/// Small looping render of a clip's in/out range, shown in the corner of the preview.
+/// The current loop keeps playing while a new one renders, so trims can be tweaked
+/// without the loop stopping; at most one render runs at a time. The player streams frames
+/// without keeping them, so there is no frame cache to draw from and each range is decoded
+/// here at loop size.
+pub struct LoopPreview {
+    key: Option<LoopKey>,
+    frames: Vec<egui::TextureHandle>,
+    pending: Option<(LoopKey, mpsc::Receiver<Option<Vec<egui::ColorImage>>>)>,
+    started: Instant,
+}
+
+impl LoopPreview {
+    pub fn new() -> Self {
+        Self { key: None, frames: Vec::new(), pending: None, started: Instant::now() }
+    }
+
+    /// Collects a finished render and starts another if the clip has changed since.
+    pub fn update(&mut self, ctx: &egui::Context, clip: &VideoClip) {
+        if let Some((key, receiver)) = &self.pending
+            && let Ok(result) = receiver.try_recv()
+        {
+            if let Some(images) = result {
+                self.frames = images.into_iter()
+                    .enumerate()
+                    .map(|(i, image)| ctx.load_texture(format!("loop_preview_{}", i), image, egui::TextureOptions::LINEAR))
+                    .collect();
+                self.started = Instant::now();
+            }
+            self.key = Some(key.clone());
+            self.pending = None;
+        }
+
+        let key = LoopKey {
+            path: clip.path.clone(),
+            trim_start: clip.trim_start,
+            trim_end: clip.trim_end,
+            filters: clip.preview_filters(),
+        };
+        if self.pending.is_none() && self.key.as_ref() != Some(&key) {
+            let (sender, receiver) = mpsc::channel();
+            let job = key.clone();
+            let ctx = ctx.clone();
+            thread::spawn(move || {
+                let _ = sender.send(render(&job));
+                ctx.request_repaint();
+            });
+            self.pending = Some((key, receiver));
+        }
+    }
+
+    pub fn is_rendering(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Frame for the current moment of the loop, None until the first render lands.
+    pub fn current_frame(&self) -> Option<&egui::TextureHandle> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let frame = (self.started.elapsed().as_millis() as u64 * LOOP_FPS as u64 / 1000) as usize;
+        self.frames.get(frame % self.frames.len())
+    }
+}
+
+fn render(key: &LoopKey) -> Option<Vec<egui::ColorImage>> {
+    let range_secs = key.trim_end.saturating_sub(key.trim_start) as f32 / 1000.0;
+    // a long range plays sped up rather than cut short, so the out -> in wrap is always in the loop
+    let sample_fps = (MAX_LOOP_FRAMES as f32 / range_secs.max(0.001)).min(LOOP_FPS as f32);
+    let mut child = Command::new("ffmpeg")
+        .arg("-ss").arg(format!("{:.3}", key.trim_start as f32 / 1000.0))
+        .arg("-t").arg(format!("{:.3}", range_secs))
+        .arg("-i").arg(&key.path)
+        .arg("-an")
+        .arg("-vf").arg(format!(
+            "{}fps={:.4},scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2",
+            key.filters, sample_fps, LOOP_WIDTH, LOOP_HEIGHT, LOOP_WIDTH, LOOP_HEIGHT
+        ))
+        .arg("-frames:v").arg(MAX_LOOP_FRAMES.to_string())
+        .arg("-f").arg("rawvideo")
+        .arg("-pix_fmt").arg("rgba")
+        .arg("-")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+
+    let mut buffer = vec![0u8; (LOOP_WIDTH * LOOP_HEIGHT * 4) as usize];
+    let mut frames = Vec::new();
+    while stdout.read_exact(&mut buffer).is_ok() {
+        frames.push(egui::ColorImage::from_rgba_unmultiplied([LOOP_WIDTH as usize, LOOP_HEIGHT as usize], &buffer));
+    }
+    let _ = child.wait();
+
+    (!frames.is_empty()).then_some(frames)
+}
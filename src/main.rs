@@ -1,25 +1,55 @@
 use eframe::egui;
 use rfd::FileDialog;
+use std::collections::HashMap;
 use std::process::Command;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
+mod auto_preview;
+mod card;
+mod crash;
+mod diagnostics;
+mod embedded;
+mod export;
+mod hash;
+mod letterbox;
+mod loop_preview;
+mod manifest;
+mod media_jobs;
+mod notify;
+mod outro;
+mod pipeline;
 mod player;
-use player::{PlayerCommand, VideoPlayer, PREVIEW_WIDTH, PREVIEW_HEIGHT};
+mod project;
+mod project_diff;
+mod remote;
+mod review;
+mod roundtrip;
+mod settings;
+mod shotmatch;
+mod taskbar;
+mod transfer;
+use media_jobs::{MediaJob, MediaJobResult, MediaJobs};
+use player::{PlayerCommand, ProgramOutput, VideoPlayer, PREVIEW_WIDTH, PREVIEW_HEIGHT};
 
 fn main() -> eframe::Result<()> {
+    crash::install();
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(egui::Vec2::new(800.0, 600.0));
+    if let Some([x, y, width, height]) = settings::load().window {
+        viewport = viewport.with_position(egui::pos2(x, y)).with_inner_size(egui::vec2(width, height));
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size(egui::Vec2::new(800.0, 600.0)),
+        viewport,
         ..Default::default()
     };
     eframe::run_native(
-        "Video Editor",
+        taskbar::APP_TITLE,
         options,
         Box::new(|cc| Ok(Box::new(VideoEditorApp::new(cc.egui_ctx.clone())))),
     )
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct VideoClip {
     path: PathBuf,
     name: String,
@@ -27,48 +57,456 @@ struct VideoClip {
     timeline_start: u32,
     trim_start: u32,
     trim_end: u32,
+
+    stabilize_zoom: Option<u32>, // percent cropped away to hide deshake borders
+    crop: Option<letterbox::Crop>, // removes baked-in black bars, in source pixels
+    rotation: u32, // clockwise degrees, multiple of 90
+    hflip: bool,
+    vflip: bool,
+    grade: ColorGrade,
+    generator: Option<[u8; 3]>, // solid colour clip rendered by lavfi instead of a media file
+    audio_offset: i32, // ms the audio is slipped against its video, positive = later
+    stem: Stem,
+}
+
+/// Per-channel linear colour correction (`out = in * gain + offset`, 8-bit scale).
+#[derive(Clone, Copy, PartialEq)]
+struct ColorGrade {
+    gain: [f32; 3],
+    offset: [f32; 3],
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self { gain: [1.0; 3], offset: [0.0; 3] }
+    }
+}
+
+impl ColorGrade {
+    fn filter(&self) -> String {
+        if *self == Self::default() {
+            return String::new();
+        }
+        let channel = |i: usize| format!("clip(val*{:.3}+{:.1}\\,0\\,255)", self.gain[i], self.offset[i]);
+        format!("lutrgb=r={}:g={}:b={},", channel(0), channel(1), channel(2))
+    }
+}
+
+impl VideoClip {
+    /// Crop/rotation/flip filters, shared by preview and export.
+    fn orientation_filters(&self) -> String {
+        let mut filters = self.crop.map(|c| c.filter()).unwrap_or_default();
+        filters.push_str(match self.rotation {
+            90 => "transpose=clock,",
+            180 => "hflip,vflip,",
+            270 => "transpose=cclock,",
+            _ => "",
+        });
+        if self.hflip {
+            filters.push_str("hflip,");
+        }
+        if self.vflip {
+            filters.push_str("vflip,");
+        }
+        filters
+    }
+
+    /// Filters cheap enough to run in the realtime preview decoder.
+    fn preview_filters(&self) -> String {
+        self.orientation_filters() + &self.grade.filter()
+    }
+
+    /// Audio filters that slip the audio by `audio_offset` while keeping the clip's length.
+    fn audio_filters(&self) -> String {
+        let clip_secs = (self.trim_end - self.trim_start) as f32 / 1000.0;
+        let slip = match self.audio_offset {
+            0 => return String::new(),
+            ms if ms > 0 => format!("adelay={}:all=1", ms),
+            ms => format!("atrim=start={:.3},asetpts=PTS-STARTPTS", -ms as f32 / 1000.0),
+        };
+        format!("{},apad,atrim=end={:.3},", slip, clip_secs)
+    }
+
+    /// Timeline label from a template such as "{stem} {in}-{out}"; see `CLIP_LABEL_TOKENS`.
+    /// Generators keep their own name.
+    fn label(&self, template: &str, capture_date: Option<&str>) -> String {
+        if template.trim().is_empty() || self.generator.is_some() {
+            return self.name.clone();
+        }
+        let stem = self.path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        template
+            .replace("{name}", &self.name)
+            .replace("{stem}", &stem)
+            .replace("{in}", &format_timecode(self.trim_start))
+            .replace("{out}", &format_timecode(self.trim_end))
+            .replace("{duration}", &format_timecode(self.trim_end - self.trim_start))
+            .replace("{date}", capture_date.unwrap_or(""))
+    }
+
+    /// Offset in whole timeline frames, as shown on the sync badge.
+    fn audio_offset_frames(&self) -> i32 {
+        (self.audio_offset as f32 * TIMELINE_FPS / 1000.0).round() as i32
+    }
+
+    /// Per-clip video filters applied before the export scale/pad stage.
+    fn video_filters(&self) -> String {
+        let mut filters = self.preview_filters();
+        if let Some(zoom) = self.stabilize_zoom {
+            let keep = 1.0 - zoom as f32 / 100.0;
+            filters.push_str(&format!("deshake,crop=iw*{:.3}:ih*{:.3},", keep, keep));
+        }
+        filters
+    }
+}
+
+/// An imported source file; timeline clips reference bin items by path.
+#[derive(Clone)]
+struct MediaItem {
+    path: PathBuf,
+    name: String,
+    duration: u32, // probed once at import and kept in the project; see `refresh_metadata`
+    stamp: Option<(u64, u64)>, // file size and modification time when probed
+}
+
+/// Size and modification time (unix seconds), to notice a source replaced on disk.
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    Some((meta.len(), modified))
+}
+
+impl MediaItem {
+    fn changed_on_disk(&self) -> bool {
+        self.stamp.is_some_and(|stamp| file_stamp(&self.path) != Some(stamp))
+    }
+
+    fn usage_count(&self, clips: &[VideoClip]) -> usize {
+        clips.iter().filter(|c| c.generator.is_none() && c.path == self.path).count()
+    }
+}
+
+/// Audio streams written to the export: the timeline mix plus any alternate-language files,
+/// each laid against the start of the programme and mapped as its own selectable stream.
+#[derive(Clone, Default)]
+struct AudioTracks {
+    language: String, // ISO 639-2 code for the timeline mix, empty = untagged
+    alternates: Vec<AltAudio>,
+    cut_fade_frames: u32, // audio crossfade length at every cut, 0 = hard cuts
+}
+
+impl AudioTracks {
+    /// Fades out the end of the outgoing clip and in the start of the incoming one, half the
+    /// length each, so the cut stays on the same frame in video and audio.
+    fn cut_fade_filters(&self, index: usize, clip_count: usize, clip_secs: f32) -> String {
+        if self.cut_fade_frames == 0 {
+            return String::new();
+        }
+        let half = (self.cut_fade_frames as f32 / TIMELINE_FPS / 2.0).min(clip_secs / 2.0);
+        let mut filters = String::new();
+        if index > 0 {
+            filters.push_str(&format!("afade=t=in:d={:.3},", half));
+        }
+        if index + 1 < clip_count {
+            filters.push_str(&format!("afade=t=out:st={:.3}:d={:.3},", clip_secs - half, half));
+        }
+        filters
+    }
+}
+
+#[derive(Clone)]
+struct AltAudio {
+    path: PathBuf,
+    language: String,
+}
+
+impl AltAudio {
+    fn title(&self) -> String {
+        self.path.file_stem().unwrap_or_default().to_string_lossy().into_owned()
+    }
+}
+
+/// Output channel layout; anything but `Keep` converts every clip before concat,
+/// using swresample's standard downmix matrix (centre and surrounds at -3 dB).
+#[derive(Clone, Copy, PartialEq)]
+enum ChannelLayout {
+    Keep,
+    Mono,
+    Stereo,
+    Surround51,
+}
+
+impl ChannelLayout {
+    const ALL: [ChannelLayout; 4] = [ChannelLayout::Keep, ChannelLayout::Mono, ChannelLayout::Stereo, ChannelLayout::Surround51];
+
+    fn label(self) -> &'static str {
+        match self {
+            ChannelLayout::Keep => "Keep source",
+            ChannelLayout::Mono => "Mono",
+            ChannelLayout::Stereo => "Stereo",
+            ChannelLayout::Surround51 => "5.1",
+        }
+    }
+
+    fn filter(self) -> &'static str {
+        match self {
+            ChannelLayout::Keep => "anull",
+            ChannelLayout::Mono => "aformat=channel_layouts=mono",
+            ChannelLayout::Stereo => "aformat=channel_layouts=stereo",
+            ChannelLayout::Surround51 => "aformat=channel_layouts=5.1",
+        }
+    }
+}
+
+/// Which audio stem a clip's sound belongs to when stems are exported for mixing.
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Stem {
+    #[default]
+    Dialog,
+    Music,
+    Effects,
+}
+
+impl Stem {
+    const ALL: [Stem; 3] = [Stem::Dialog, Stem::Music, Stem::Effects];
+
+    fn label(self) -> &'static str {
+        match self {
+            Stem::Dialog => "Dialog",
+            Stem::Music => "Music",
+            Stem::Effects => "SFX",
+        }
+    }
+
+    /// Used in project files and stem file names.
+    fn key(self) -> &'static str {
+        match self {
+            Stem::Dialog => "dialog",
+            Stem::Music => "music",
+            Stem::Effects => "sfx",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TrimSide {
+    In,
+    Out,
+}
+
+enum GapAction {
+    Resize(u32),
+    Fill,
+}
+
+#[derive(Clone)]
+struct Marker {
+    time: u32,
+    note: String,
 }
 
 struct VideoEditorApp {
+    media_bin: Vec<MediaItem>,
+    bin_unused_only: bool,
+    selected_bin_item: Option<PathBuf>,
+    overwrite_mode: bool, // bin items replace what is under them instead of pushing it right
+    confirm_remove_unused: Option<bool>, // confirmation open; true = also move the files
+    crash_report: Option<PathBuf>, // report from the last run, offered once
+    compare_with: Option<(PathBuf, Vec<VideoClip>)>, // saved version shown in the compare window
+    auto_preview: auto_preview::AutoPreview,
+    review_target: (Option<PathBuf>, u32), // preview folder and minutes between renders, from settings
+    embedded_import: Option<EmbeddedImport>,
+    subtitle_extraction: Option<(std::sync::mpsc::Receiver<embedded::Extracted>, usize)>, // outcome + chapter markers added
+    card_import: Option<Vec<(card::CardClip, bool)>>, // clips found on a card, with selection
+    card_copy: Option<(std::sync::mpsc::Receiver<transfer::TransferEvent>, f32, PathBuf)>, // events + progress + media folder
+    media_move: Option<(std::sync::mpsc::Receiver<transfer::TransferEvent>, f32)>, // unused media being moved out
     clips: Vec<VideoClip>,
+    markers: Vec<Marker>,
+    audio_tracks: AudioTracks,
+    outro: outro::OutroTemplate,
+    outro_render: Option<(std::sync::mpsc::Receiver<Result<(), &'static str>>, PathBuf)>, // appended once rendered
+    poster_frame: Option<u32>, // timeline ms, grabbed after export
     total_timeline_duration: u32,
+    timeline_view_start: u32, // visible window of the timeline, in ms
+    timeline_view_len: u32,
+    auto_zoom_on_drag: bool,
+    zoom_before_drag: Option<u32>, // view length to restore after a drag zoomed out
     playhead: u32,
     is_exporting: bool,
     status_message: String,
 
     video_player: VideoPlayer,
     current_preview_texture: Option<egui::TextureHandle>,
+    preview_detached: bool, // shown in its own OS window, e.g. on a second monitor
+    window_geometry: Option<[f32; 4]>, // last seen main window position/size, saved on exit
+    compare_snapshot: Option<(egui::TextureHandle, u32)>, // frame + timeline ms it was taken at
+    loop_preview: Option<loop_preview::LoopPreview>, // corner loop of the selected clip, when on
+    compare_wipe: f32, // 0..1 across the preview, snapshot is shown left of it
     last_requested_playhead_ms: u32,
     last_playhead_update_time: Instant,
     current_active_clip_id: Option<usize>,
 
     is_playing: bool,
+    low_power: bool, // battery saver: smaller/slower preview, background jobs paused
     last_play_update_time: Instant,
     
     pending_clip_transition: bool,
 
-    clip_drag_init: u32,
+    clip_drag_init: u32, // ms between clip start and the grab point
+    drag_origin: Option<(u32, u32, u32)>, // timeline_start, trim_start, trim_end when a drag began
     selected_clip: Option<usize>, // index
+    selected_gap: Option<(u32, u32)>, // timeline start/end of the selected empty span
+    trim_mode: bool,
+    trim_point: Option<(usize, TrimSide)>, // edit point nudged by the keyboard in trim mode
+    trim_frames: Option<[Option<egui::TextureHandle>; 2]>, // outgoing last / incoming first frame at the trim point
+    proposed_crop: Option<(usize, letterbox::Crop)>, // detected bars awaiting confirmation
+    crop_detection: Option<letterbox::CropDetection>, // "Detect Black Bars" running in the background
+    shot_match: Option<shotmatch::ShotMatch>, // "Match to…" sampling in the background
+
+    project_path: Option<PathBuf>,
+    project_read_only: bool, // opened as a copy; Save is blocked until Save As
+    recent_projects: Vec<RecentProject>,
+
+    diagnostics_report: Option<String>,
+    settings_window: Option<settings::Settings>, // edited copy while the window is open
+    clip_label: String, // timeline label template, from settings
+    round_trips: Vec<roundtrip::RoundTrip>, // clips out at the compositor, waiting for a render
+    round_trips_sending: Vec<std::sync::mpsc::Receiver<Result<roundtrip::RoundTrip, &'static str>>>, // intermediates being written
+    capture_dates: HashMap<PathBuf, Option<String>>, // None while the job is pending or if there is no date
+    chapters_text: Option<String>,
+    write_chapters_on_export: bool,
+    write_manifest_on_export: bool,
+    channel_layout: ChannelLayout,
+    export_video: bool,
+    notify_on_export: bool,
+    export_stems: bool,
+    remote_target: remote::RemoteTarget,
+    remote_render: Option<(std::sync::mpsc::Receiver<remote::RemoteEvent>, f32)>, // events + progress
+    local_export: Option<(std::sync::mpsc::Receiver<export::ExportEvent>, f32, PathBuf)>, // events + progress + output
+    taskbar_percent: Option<u32>, // last progress shown on the taskbar/dock
+    pipeline_file: Option<PathBuf>, // last pipeline run, for the file dialog
+    export_audio: bool,
+
+    program_output: ProgramOutput,
+    program_output_enabled: bool,
+
+    media_jobs: MediaJobs,
+    waveforms: HashMap<PathBuf, Option<media_jobs::Waveform>>, // None while the job is pending or if it failed
+    thumbnails: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    proxies: HashMap<PathBuf, Option<PathBuf>>, // source -> proxy file, None when there is none yet
+    proxies_pending: usize,
+    use_proxies: bool, // preview only; export always reads the originals
+    video_track: TrackSettings,
+}
+
+struct TrackSettings {
+    height: f32,
+    show_waveform: bool,
+    show_thumbnails: bool,
+}
+
+/// Chapters/subtitles found in a just-imported file, waiting for the user to accept them.
+struct EmbeddedImport {
+    path: PathBuf,
+    timeline_start: u32,
+    duration: u32,
+    data: embedded::EmbeddedData,
+    add_chapters: bool,
+    extract_subtitles: bool,
+}
+
+struct RecentProject {
+    path: PathBuf,
+    info: Option<project::ProjectInfo>,
+    poster: Option<egui::TextureHandle>,
 }
 
 impl VideoEditorApp {
     fn new(ctx: egui::Context) -> Self {
+        let recent_projects = load_recent_projects(&ctx, project::load_recent());
         Self {
+            media_bin: Vec::new(),
+            bin_unused_only: false,
+            selected_bin_item: None,
+            overwrite_mode: false,
+            confirm_remove_unused: None,
+            crash_report: crash::pending_report(),
+            compare_with: None,
+            auto_preview: auto_preview::AutoPreview::new(),
+            review_target: { let s = settings::load(); (s.review_dir, s.review_minutes) },
+            embedded_import: None,
+            subtitle_extraction: None,
+            card_import: None,
+            card_copy: None,
+            media_move: None,
             clips: Vec::new(),
+            markers: Vec::new(),
+            audio_tracks: AudioTracks::default(),
+            outro: outro::OutroTemplate::default(),
+            outro_render: None,
+            poster_frame: None,
             total_timeline_duration: 30 * 1000,
+            timeline_view_start: 0,
+            timeline_view_len: 30 * 1000,
+            auto_zoom_on_drag: false,
+            zoom_before_drag: None,
             playhead: 0,
             is_exporting: false,
             status_message: String::new(),
-            video_player: VideoPlayer::new(ctx),
+            video_player: VideoPlayer::new(ctx.clone()),
             current_preview_texture: None,
+            preview_detached: false,
+            window_geometry: None,
+            compare_snapshot: None,
+            loop_preview: None,
+            compare_wipe: 0.5,
             last_requested_playhead_ms: 0,
             last_playhead_update_time: Instant::now(),
             current_active_clip_id: None,
             is_playing: false,
+            low_power: false,
             last_play_update_time: Instant::now(),
             pending_clip_transition: false,
             clip_drag_init: 0,
+            drag_origin: None,
             selected_clip: None,
+            selected_gap: None,
+            trim_mode: false,
+            trim_point: None,
+            trim_frames: None,
+            proposed_crop: None,
+            crop_detection: None,
+            shot_match: None,
+            project_path: None,
+            project_read_only: false,
+            recent_projects,
+            diagnostics_report: None,
+            settings_window: None,
+            clip_label: settings::load().clip_label,
+            round_trips: Vec::new(),
+            round_trips_sending: Vec::new(),
+            capture_dates: HashMap::new(),
+            chapters_text: None,
+            write_chapters_on_export: false,
+            write_manifest_on_export: false,
+            channel_layout: ChannelLayout::Keep,
+            export_video: true,
+            notify_on_export: true,
+            export_stems: false,
+            remote_target: settings::load().remote,
+            remote_render: None,
+            local_export: None,
+            taskbar_percent: None,
+            pipeline_file: None,
+            export_audio: true,
+            program_output: ProgramOutput { format: player::PROGRAM_OUTPUT_FORMATS[0].to_string(), device: String::new() },
+            program_output_enabled: false,
+            media_jobs: MediaJobs::new(ctx.clone()),
+            waveforms: HashMap::new(),
+            thumbnails: HashMap::new(),
+            proxies: HashMap::new(),
+            proxies_pending: 0,
+            use_proxies: false,
+            video_track: TrackSettings { height: 60.0, show_waveform: false, show_thumbnails: false },
         }
     }
 }
@@ -76,14 +514,50 @@ impl VideoEditorApp {
 impl Drop for VideoEditorApp {
     fn drop(&mut self) {
         self.video_player.send_command(PlayerCommand::Stop);
+        if self.window_geometry.is_some() {
+            let mut settings = settings::load();
+            settings.window = self.window_geometry;
+            let _ = settings::save(&settings);
+        }
     }
 }
 
+const CLIP_LABEL_TOKENS: &str = "{name} {stem} {in} {out} {duration} {date}";
 const MIN_CLIP_DURATION: u32 = 100;
+const TIMELINE_FPS: f32 = 30.0; // export frame rate, also the trim nudge step
+
+fn format_timecode(ms: u32) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+fn format_precise_timecode(ms: u32) -> String {
+    format!("{}.{:03}", format_timecode(ms), ms % 1000)
+}
+
+/// Signed difference for edit read-outs, e.g. "+00:00:12.000".
+fn format_delta(from: u32, to: u32) -> String {
+    if to >= from {
+        format!("+{}", format_precise_timecode(to - from))
+    } else {
+        format!("-{}", format_precise_timecode(from - to))
+    }
+}
+
+fn load_recent_projects(ctx: &egui::Context, paths: Vec<PathBuf>) -> Vec<RecentProject> {
+    paths.into_iter().map(|path| {
+        let info = project::read_info(&path).ok();
+        let poster = info.as_ref()
+            .and_then(|i| i.poster_png.as_deref())
+            .and_then(project::poster_to_image)
+            .map(|image| ctx.load_texture(format!("poster_{}", path.display()), image, egui::TextureOptions::LINEAR));
+        RecentProject { path, info, poster }
+    }).collect()
+}
 
 fn get_video_duration(path: &PathBuf) -> Result<u32, &str> {
     let output = Command::new("ffprobe")
-        .args(&[
+        .args([
             "-v", "error",
             "-show_entries", "format=duration",
             "-of", "default=noprint_wrappers=1:nokey=1",
@@ -101,146 +575,821 @@ fn get_video_duration(path: &PathBuf) -> Result<u32, &str> {
     Ok((duration_secs * 1000.0) as u32)
 }
 
+/// Writes `<output>.png` from the exported file and embeds it as the MP4 cover thumbnail.
+fn write_poster(output: &Path, poster_ms: u32) -> Result<(), &'static str> {
+    let poster_path = output.with_extension("png");
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-ss").arg(format!("{:.3}", poster_ms as f32 / 1000.0))
+        .arg("-i").arg(output)
+        .arg("-frames:v").arg("1")
+        .arg(&poster_path)
+        .status()
+        .map_err(|_| "Error running ffmpeg for poster frame")?;
+    if !status.success() {
+        return Err("Exported, but writing the poster frame failed");
+    }
+
+    let remuxed = output.with_extension("poster.mp4");
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i").arg(output)
+        .arg("-i").arg(&poster_path)
+        .args(["-map", "0", "-map", "1", "-c", "copy", "-disposition:v:1", "attached_pic"])
+        .arg(&remuxed)
+        .status()
+        .map_err(|_| "Error running ffmpeg for poster frame")?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&remuxed);
+        return Err("Exported with poster PNG, but embedding the thumbnail failed");
+    }
+    std::fs::rename(&remuxed, output).map_err(|_| "Exported with poster PNG, but embedding the thumbnail failed")
+}
+
 impl eframe::App for VideoEditorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("test");
+        if let Some(report) = &self.diagnostics_report {
+            let mut open = true;
+            let mut rerun = false;
+            egui::Window::new("Diagnostics").open(&mut open).show(ctx, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut report.as_str()).code_editor().desired_width(500.0));
+                ui.horizontal(|ui| {
+                    if ui.button("Copy Report").clicked() {
+                        ctx.copy_text(report.clone());
+                    }
+                    rerun = ui.button("Re-run").clicked();
+                });
+            });
+            if !open {
+                self.diagnostics_report = None;
+            } else if rerun {
+                self.diagnostics_report = Some(diagnostics::capability_report(&settings::cache_dir()));
+            }
+        }
 
-            ui.horizontal(|ui| { // toolbar
-                if ui.button("Import").clicked() {
-                    if let Some(path) = FileDialog::new()
-                        .add_filter("Video", &["mp4", "mkv", "mov"])
-                        .pick_file() 
+        if let Some(edited) = &mut self.settings_window {
+            let mut open = true;
+            let mut apply = false;
+            egui::Window::new("Settings").open(&mut open).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Cache folder:");
+                    let shown = edited.cache_dir.clone().unwrap_or_else(settings::default_cache_dir);
+                    ui.monospace(shown.display().to_string());
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Browse…").clicked()
+                        && let Some(dir) = FileDialog::new().pick_folder()
                     {
-                        let name = path.file_name().unwrap().to_string_lossy().into_owned();
-                        
-                        let duration = match get_video_duration(&path) {
-                            Ok(dur) => dur,
-                            Err(err) => {
-                                self.set_status(err);
-                                10000
-                            },
-                        };
-                        
-                        let offset = self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
-
-                        self.clips.push(VideoClip {
-                            path,
-                            name,
-                            duration,
-                            timeline_start: offset,
-                            trim_start: 0,
-                            trim_end: duration,
-                        });
-                        self.set_status("Clip added to timeline.");
+                        edited.cache_dir = Some(dir);
                     }
-                }
+                    if ui.add_enabled(edited.cache_dir.is_some(), egui::Button::new("Use Default")).clicked() {
+                        edited.cache_dir = None;
+                    }
+                });
 
-                if !self.clips.is_empty() {
-                    if ui.button("Export All").clicked() {
-                        if let Some(output) = FileDialog::new()
-                            .add_filter("MP4", &["mp4"])
-                            .save_file() 
-                        {
-                            self.export_sequence(output);
-                        }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Clip labels:");
+                    ui.add(egui::TextEdit::singleline(&mut edited.clip_label).hint_text("{name}"))
+                        .on_hover_text(format!("Shown on timeline clips. Available: {}", CLIP_LABEL_TOKENS));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Review preview:");
+                    match &edited.review_dir {
+                        Some(dir) => { ui.monospace(dir.display().to_string()); }
+                        None => { ui.weak("off"); }
                     }
-                    if ui.button("Clear").clicked() {
-                        self.clips.clear();
-                        // self.clips.clear();
-                        self.playhead = 0;
-                        self.video_player.send_command(PlayerCommand::StopPlayback);
-                        self.is_playing = false;
+                    if ui.button("Choose Folder…").clicked()
+                        && let Some(dir) = FileDialog::new().pick_folder()
+                    {
+                        edited.review_dir = Some(dir);
+                        edited.review_minutes = edited.review_minutes.max(10);
                     }
-                }
+                    if edited.review_dir.is_some() {
+                        ui.add(egui::DragValue::new(&mut edited.review_minutes).range(1..=240).prefix("every ").suffix(" min"));
+                        if ui.small_button("Off").clicked() {
+                            edited.review_dir = None;
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Renders a 360p preview of the timeline to this folder (e.g. a synced Dropbox) whenever the cut has changed");
+                ui.horizontal(|ui| {
+                    ui.label("Compositor:");
+                    ui.add(egui::TextEdit::singleline(&mut edited.compositor).hint_text("e.g. blender or natron"))
+                        .on_hover_text("Program that opens clips sent to the compositor; leave empty to open the folder");
+                });
 
                 ui.separator();
+                let mut portable = settings::portable_root().is_some();
+                if ui.checkbox(&mut portable, "Portable mode")
+                    .on_hover_text("Keep settings and caches in a folder next to the executable")
+                    .changed()
+                    && let Err(err) = settings::set_portable(portable)
+                {
+                    self.status_message = err.to_string();
+                }
+                if let Some(dir) = settings::config_dir() {
+                    ui.label(format!("Settings are stored in {}", dir.display()));
+                }
 
-                if ui.button(if self.is_playing { "⏸ Pause" } else { "▶ Play" }).clicked() {
-                    self.is_playing = !self.is_playing;
-                    self.last_play_update_time = Instant::now();
-
-                    let active_clip_idx = self.clips.iter().position(|c| {
-                        let clip_timeline_end = c.timeline_start + (c.trim_end - c.trim_start);
-                        self.playhead >= c.timeline_start && self.playhead < clip_timeline_end
-                    });
+                ui.separator();
+                apply = ui.button("Save").clicked();
+            });
+            if apply {
+                match settings::save(edited) {
+                    Ok(()) => {
+                        self.clip_label = edited.clip_label.clone();
+                        self.review_target = (edited.review_dir.clone(), edited.review_minutes);
+                        self.status_message = "Settings saved.".to_string();
+                    }
+                    Err(err) => self.status_message = err.to_string(),
+                }
+            }
+            if !open || apply {
+                self.settings_window = None;
+            }
+        }
 
-                    if let Some(idx) = active_clip_idx {
-                        if self.is_playing {
-                            let active_clip = &self.clips[idx];
-                            let clip_playhead_offset_ms = self.playhead - active_clip.timeline_start;
-                            
-                            // very unoptimized (temp)
-                            self.video_player.send_command(PlayerCommand::LoadClip {
-                                path: active_clip.path.clone(),
-                                trim_start_ms: active_clip.trim_start,
-                                trim_end_ms: active_clip.trim_end,
-                            });
+        if let Some(report) = &self.crash_report {
+            let mut open = true;
+            let mut open_report = false;
+            let mut dismissed = false;
+            egui::Window::new("Video Editor Crashed").open(&mut open).collapsible(false).show(ctx, |ui| {
+                ui.label("The editor closed unexpectedly last time. A report was saved with the recent actions, the timeline layout (file paths anonymized) and a backtrace.");
+                ui.weak(report.display().to_string());
+                ui.horizontal(|ui| {
+                    open_report = ui.button("Open Report").clicked();
+                    dismissed = ui.button("Dismiss").clicked();
+                });
+            });
+            if open_report || dismissed || !open {
+                let report = crash::acknowledge(report);
+                if open_report {
+                    notify::open_path(&report);
+                }
+                self.crash_report = None;
+            }
+        }
 
-                            self.video_player.send_command(PlayerCommand::StartPlayback { 
-                                timestamp_ms: clip_playhead_offset_ms 
-                            });
-                        } else {
-                            self.video_player.send_command(PlayerCommand::StopPlayback);
-                        }
+        if let Some((saved_path, saved)) = &self.compare_with {
+            let mut open = true;
+            let mut jump_to = None;
+            let rows = project_diff::diff(saved, &self.clips);
+            let title = format!("Compare with {}", saved_path.file_name().unwrap_or_default().to_string_lossy());
+            egui::Window::new(title).id(egui::Id::new("compare_projects")).open(&mut open).default_width(520.0).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for change in [project_diff::Change::Added, project_diff::Change::Removed, project_diff::Change::Moved, project_diff::Change::Retrimmed] {
+                        let count = rows.iter().filter(|r| r.change == change).count();
+                        ui.colored_label(change.color(), format!("{} {}", count, change.label()));
                     }
-                    
-                    ctx.request_repaint();
-                }
+                });
 
-                if ui.button("⏪ 5s").clicked() {
-                    self.playhead = self.playhead.saturating_sub(5000);
-                    self.last_play_update_time = Instant::now();
-                    self.last_requested_playhead_ms = u32::MAX;
-                    
-                    // Stop playback if currently playing
-                    if self.is_playing {
-                        self.is_playing = false;
-                        self.video_player.send_command(PlayerCommand::StopPlayback);
+                // saved lane on top, current lane below, on a shared time scale
+                let end = saved.iter().chain(&self.clips).map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(1, u32::max);
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 56.0), egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 2.0, egui::Color32::from_gray(30));
+                let lane = |clip: &VideoClip, top: f32| {
+                    let x = |t: u32| rect.left() + t as f32 / end as f32 * rect.width();
+                    egui::Rect::from_x_y_ranges(x(clip.timeline_start)..=x(clip.timeline_start + (clip.trim_end - clip.trim_start)), top..=top + 24.0)
+                };
+                for row in &rows {
+                    if let Some(o) = row.old {
+                        painter.rect_filled(lane(&saved[o], rect.top() + 2.0).shrink2(egui::vec2(0.5, 0.0)), 2.0, row.change.color());
                     }
-                    
-                    ctx.request_repaint();
-                }
-                if ui.button("⏩ 5s").clicked() {
-                    self.playhead = (self.playhead + 5000).min(self.total_timeline_duration);
-                    self.last_play_update_time = Instant::now();
-                    self.last_requested_playhead_ms = u32::MAX;
-                    
-                    // Stop playback if currently playing
-                    if self.is_playing {
-                        self.is_playing = false;
-                        self.video_player.send_command(PlayerCommand::StopPlayback);
+                    if let Some(n) = row.new {
+                        painter.rect_filled(lane(&self.clips[n], rect.top() + 30.0).shrink2(egui::vec2(0.5, 0.0)), 2.0, row.change.color());
                     }
-                    
-                    ctx.request_repaint();
                 }
+                painter.text(rect.left_top() + egui::vec2(4.0, 4.0), egui::Align2::LEFT_TOP, "saved", egui::FontId::proportional(10.0), egui::Color32::WHITE);
+                painter.text(rect.left_top() + egui::vec2(4.0, 32.0), egui::Align2::LEFT_TOP, "current", egui::FontId::proportional(10.0), egui::Color32::WHITE);
+
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    for row in rows.iter().filter(|r| r.change != project_diff::Change::Unchanged) {
+                        let describe = |clip: &VideoClip| format!(
+                            "at {} [{}–{}]",
+                            format_precise_timecode(clip.timeline_start), format_precise_timecode(clip.trim_start), format_precise_timecode(clip.trim_end)
+                        );
+                        let clip = row.new.map(|n| &self.clips[n]).or(row.old.map(|o| &saved[o])).unwrap();
+                        ui.horizontal(|ui| {
+                            ui.colored_label(row.change.color(), row.change.label());
+                            let name = ui.add(egui::Label::new(&clip.name).sense(egui::Sense::click()));
+                            if let Some(n) = row.new
+                                && name.on_hover_text("Select on the timeline").clicked()
+                            {
+                                jump_to = Some(n);
+                            }
+                            match (row.old, row.new) {
+                                (Some(o), Some(n)) => ui.weak(format!("{} → {}", describe(&saved[o]), describe(&self.clips[n]))),
+                                _ => ui.weak(describe(clip)),
+                            };
+                        });
+                    }
+                });
             });
+            if let Some(n) = jump_to {
+                self.selected_clip = Some(n);
+                self.playhead = self.clips[n].timeline_start;
+            }
+            if !open {
+                self.compare_with = None;
+            }
+        }
 
-            ui.separator();
-
-            // move playhead through time
-            if self.is_playing {
-                let elapsed_ms = self.last_play_update_time.elapsed().as_millis() as u32;
-                if elapsed_ms > 0 {
-                    self.playhead = (self.playhead + elapsed_ms).min(self.total_timeline_duration);
-                    self.last_play_update_time = Instant::now();
-                }   
-
-                // reached  end of timeline
-                if self.playhead >= self.total_timeline_duration {
-                    self.playhead = self.total_timeline_duration;
-                    self.is_playing = false;
-                    self.video_player.send_command(PlayerCommand::StopPlayback);
+        if let Some(mut move_files) = self.confirm_remove_unused {
+            let unused: Vec<MediaItem> = self.media_bin.iter().filter(|m| m.usage_count(&self.clips) == 0).cloned().collect();
+            let mut open = true;
+            let mut confirmed = false;
+            egui::Window::new("Remove Unused Media").open(&mut open).collapsible(false).show(ctx, |ui| {
+                ui.label(format!("Remove {} unused item(s) from the media bin?", unused.len()));
+                for item in &unused {
+                    ui.weak(item.path.display().to_string());
                 }
+                ui.checkbox(&mut move_files, "Also move the files to a folder…");
+                ui.horizontal(|ui| {
+                    confirmed = ui.add_enabled(!unused.is_empty(), egui::Button::new("Remove")).clicked();
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_remove_unused = None;
+                    }
+                });
+            });
+            if self.confirm_remove_unused.is_some() {
+                self.confirm_remove_unused = open.then_some(move_files);
+            }
+            if confirmed {
+                self.confirm_remove_unused = None;
+                self.remove_unused_media(ctx, &unused, move_files);
             }
+        }
 
-            // preview display
-            let preview_rect_size = egui::vec2(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32);
-            let (preview_resp, painter) = ui.allocate_painter(
-                preview_rect_size,
-                egui::Sense::hover(),
-            );
-            painter.rect_filled(preview_resp.rect, 0.0, egui::Color32::from_black_alpha(200));
+        if let Some(pending) = &mut self.embedded_import {
+            let mut open = true;
+            let mut accepted = false;
+            let mut skipped = false;
+            let name = pending.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            egui::Window::new("Embedded Chapters & Subtitles").open(&mut open).collapsible(false).show(ctx, |ui| {
+                ui.label(format!("{} contains data that is not imported with the clip:", name));
+                if !pending.data.chapters.is_empty() {
+                    ui.checkbox(&mut pending.add_chapters, format!("Add {} chapter(s) as markers", pending.data.chapters.len()));
+                }
+                let text_subtitles = pending.data.subtitles.iter().filter(|s| s.is_text()).count();
+                if text_subtitles > 0 {
+                    ui.checkbox(&mut pending.extract_subtitles, format!("Extract {} subtitle stream(s) to SRT next to the source", text_subtitles));
+                }
+                for stream in pending.data.subtitles.iter().filter(|s| !s.is_text()) {
+                    ui.weak(format!("Stream {} ({}) is image-based and cannot be extracted as text", stream.index, stream.codec));
+                }
+                ui.horizontal(|ui| {
+                    accepted = ui.button("Import").clicked();
+                    skipped = ui.button("Skip").clicked();
+                });
+            });
+            if accepted {
+                let pending = self.embedded_import.take().unwrap();
+                self.import_embedded(ctx, pending);
+            } else if !open || skipped {
+                self.embedded_import = None;
+            }
+        }
+
+        if let Some(card_clips) = &mut self.card_import {
+            let mut open = true;
+            let mut import = false;
+            egui::Window::new("Import from Camera Card").open(&mut open).default_height(400.0).show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    for (clip, selected) in card_clips.iter_mut() {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(selected, "");
+                            match self.thumbnails.get(&clip.path) {
+                                Some(Some(texture)) => { ui.add(egui::Image::new((texture.id(), egui::vec2(80.0, 45.0)))); }
+                                Some(None) => { ui.allocate_exact_size(egui::vec2(80.0, 45.0), egui::Sense::hover()); }
+                                None => {
+                                    self.thumbnails.insert(clip.path.clone(), None);
+                                    self.media_jobs.send_job(MediaJob::Thumbnail { path: clip.path.clone(), source_ms: 0 });
+                                    ui.allocate_exact_size(egui::vec2(80.0, 45.0), egui::Sense::hover());
+                                }
+                            }
+                            ui.label(format!(
+                                "{}\n{} · {:.1} MB",
+                                clip.path.file_name().unwrap_or_default().to_string_lossy(),
+                                clip.layout,
+                                clip.size as f64 / 1_000_000.0
+                            ));
+                        });
+                    }
+                });
+                ui.separator();
+                let count = card_clips.iter().filter(|(_, selected)| *selected).count();
+                ui.horizontal(|ui| {
+                    if ui.button("Select All").clicked() {
+                        card_clips.iter_mut().for_each(|(_, selected)| *selected = true);
+                    }
+                    if ui.button("Select None").clicked() {
+                        card_clips.iter_mut().for_each(|(_, selected)| *selected = false);
+                    }
+                    import = ui.add_enabled(count > 0, egui::Button::new(format!("Copy & Import {} Clip(s)", count))).clicked();
+                });
+            });
+            if import {
+                let clips = self.card_import.take().unwrap();
+                self.import_from_card(ctx, clips.into_iter().filter(|(_, selected)| *selected).map(|(clip, _)| clip).collect());
+            } else if !open {
+                self.card_import = None;
+            }
+        }
+
+        if let Some(text) = &self.chapters_text {
+            let mut open = true;
+            egui::Window::new("YouTube Chapters").open(&mut open).show(ctx, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut text.as_str()).code_editor().desired_width(400.0));
+                if ui.button("Copy").clicked() {
+                    ctx.copy_text(text.clone());
+                }
+            });
+            if !open {
+                self.chapters_text = None;
+            }
+        }
+
+        self.update_background_jobs();
+
+        if let Some((events, progress)) = &mut self.remote_render {
+            let mut finished = None;
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    remote::RemoteEvent::Status(status) => self.status_message = status,
+                    remote::RemoteEvent::Progress(fraction) => *progress = fraction,
+                    remote::RemoteEvent::Finished(result) => finished = Some(result),
+                }
+            }
+            if let Some(result) = finished {
+                self.remote_render = None;
+                match result {
+                    Ok(()) => self.set_status("Remote render downloaded."),
+                    Err(err) => self.set_status(&err),
+                }
+            }
+        }
+        if let Some((events, progress, _)) = &mut self.card_copy {
+            let mut status = None;
+            let mut finished = None;
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    transfer::TransferEvent::Progress(text, fraction) => (status, *progress) = (Some(text), fraction),
+                    transfer::TransferEvent::Finished { done, failed, error } => finished = Some((done, failed, error)),
+                }
+            }
+            if let Some(status) = status {
+                self.set_status(&status);
+            }
+            if let Some((done, failed, error)) = finished
+                && let Some((_, _, media_dir)) = self.card_copy.take()
+            {
+                self.finish_card_copy(&media_dir, done, failed, error);
+            }
+        }
+        if let Some((events, progress)) = &mut self.media_move {
+            let mut status = None;
+            let mut finished = None;
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    transfer::TransferEvent::Progress(text, fraction) => (status, *progress) = (Some(text), fraction),
+                    transfer::TransferEvent::Finished { done, failed, error } => finished = Some((done, failed, error)),
+                }
+            }
+            if let Some(status) = status {
+                self.set_status(&status);
+            }
+            if let Some((moved, failed, error)) = finished {
+                self.media_move = None;
+                self.finish_media_move(moved, failed, error);
+            }
+        }
+        if let Some((result, _)) = &self.outro_render
+            && let Ok(result) = result.try_recv()
+            && let Some((_, output)) = self.outro_render.take()
+        {
+            match result {
+                Ok(()) => {
+                    self.import_media(output);
+                    self.set_status("Outro added to the end of the timeline.");
+                }
+                Err(err) => self.set_status(err),
+            }
+        }
+        if let Some((result, _)) = &self.subtitle_extraction
+            && let Ok(extracted) = result.try_recv()
+            && let Some((_, chapters)) = self.subtitle_extraction.take()
+        {
+            self.finish_embedded_import(chapters, extracted);
+        }
+        if let Some(detection) = &self.crop_detection
+            && let Some(result) = detection.poll()
+        {
+            let detection = self.crop_detection.take().unwrap();
+            let target = self.clips.iter().position(|c| {
+                c.generator.is_none() && c.path == detection.target_path && (c.trim_start, c.trim_end) == detection.target_trim
+            });
+            match (result, target) {
+                (Ok(Some(crop)), Some(idx)) if Some(crop) != self.clips[idx].crop => self.proposed_crop = Some((idx, crop)),
+                (Ok(_), Some(_)) => self.set_status("No black bars to remove."),
+                (Ok(_), None) => self.set_status("Black bars detected, but the clip has since been changed or removed"),
+                (Err(err), _) => self.set_status(err),
+            }
+        }
+        if let Some(shot_match) = &self.shot_match
+            && let Some(proposal) = shot_match.poll()
+        {
+            let shot_match = self.shot_match.take().unwrap();
+            let target = self.clips.iter_mut().find(|c| {
+                c.generator.is_none() && c.path == shot_match.target_path && (c.trim_start, c.trim_end) == shot_match.target_trim
+            });
+            match (proposal, target) {
+                (Some(grade), Some(clip)) => {
+                    clip.grade = grade;
+                    // reload so the preview picks up the new filters
+                    self.current_active_clip_id = None;
+                    self.set_status(&format!("Matched grade to {}.", shot_match.reference_name));
+                }
+                (Some(_), None) => self.set_status("Grade matched, but its clip has since been changed or removed"),
+                (None, _) => self.set_status("Could not sample frames for shot matching."),
+            }
+        }
+        if let Some((events, progress, _)) = &mut self.local_export {
+            let mut statuses = Vec::new();
+            let mut finished = None;
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    export::ExportEvent::Status(status) => statuses.push(status),
+                    export::ExportEvent::Progress(fraction) => *progress = fraction,
+                    export::ExportEvent::Finished(success) => finished = Some(success),
+                }
+            }
+            for status in statuses {
+                self.set_status(&status);
+            }
+            if let Some(success) = finished
+                && let Some((_, _, output)) = self.local_export.take()
+            {
+                if self.notify_on_export {
+                    notify::export_finished(&output, success);
+                }
+                self.is_exporting = false;
+                self.update_background_jobs();
+            }
+        }
+        let mut i = 0;
+        while i < self.round_trips_sending.len() {
+            match self.round_trips_sending[i].try_recv() {
+                Ok(sent) => {
+                    self.round_trips_sending.remove(i);
+                    self.round_trip_sent(sent);
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.round_trips_sending.remove(i);
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => i += 1,
+            }
+        }
+        let mut i = 0;
+        while i < self.round_trips.len() {
+            match self.round_trips[i].poll() {
+                Some(result) => {
+                    let round_trip = self.round_trips.remove(i);
+                    self.finish_round_trip(round_trip, result);
+                }
+                None => i += 1,
+            }
+        }
+        if !self.round_trips.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+
+        if let Some(success) = self.auto_preview.poll() {
+            self.set_status(if success { "Review preview updated." } else { "Review preview render failed." });
+        }
+        if let (Some(dir), minutes) = &self.review_target
+            && *minutes > 0
+            && !self.clips.is_empty()
+        {
+            match self.auto_preview.due_in(std::time::Duration::from_secs(*minutes as u64 * 60)) {
+                Some(wait) => ctx.request_repaint_after(wait),
+                // wait for playback or an export to finish rather than competing with it
+                None if self.is_playing || self.is_exporting => ctx.request_repaint_after(std::time::Duration::from_secs(5)),
+                None => {
+                    let name = auto_preview::preview_name(self.project_path.as_ref());
+                    let args = self.render_args(&dir.join(&name), true, true, None);
+                    if let Err(err) = self.auto_preview.start(ctx, &args, dir, &name) {
+                        self.set_status(err);
+                    }
+                }
+            }
+        }
+
+        let percent = self.local_export.as_ref().map(|(_, progress, _)| *progress)
+            .or(self.remote_render.as_ref().map(|(_, progress)| *progress))
+            .map(|progress| (progress * 100.0) as u32);
+        if percent != self.taskbar_percent {
+            self.taskbar_percent = percent;
+            taskbar::set_progress(ctx, percent);
+        }
+
+        if let (Some(outer), Some(inner)) = ctx.input(|i| (i.viewport().outer_rect, i.viewport().inner_rect)) {
+            self.window_geometry = Some([outer.min.x, outer.min.y, inner.width(), inner.height()]);
+        }
+
+        if self.preview_detached {
+            let texture = self.current_preview_texture.clone();
+            let close_requested = ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("detached_preview"),
+                egui::ViewportBuilder::default()
+                    .with_title("Preview")
+                    .with_inner_size(egui::vec2(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32)),
+                |ctx, _class| {
+                    egui::CentralPanel::default().frame(egui::Frame::NONE.fill(egui::Color32::BLACK)).show(ctx, |ui| {
+                        if let Some(texture) = &texture {
+                            let available = ui.available_rect_before_wrap();
+                            let scale = (available.width() / PREVIEW_WIDTH as f32).min(available.height() / PREVIEW_HEIGHT as f32);
+                            let rect = egui::Rect::from_center_size(available.center(), egui::vec2(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32) * scale);
+                            ui.painter().image(texture.id(), rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
+                        }
+                    });
+                    ctx.input(|i| i.viewport().close_requested())
+                },
+            );
+            if close_requested {
+                self.preview_detached = false;
+            }
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("test");
+
+            ui.horizontal_wrapped(|ui| { // toolbar
+                if ui.button("Import").clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("Video", &["mp4", "mkv", "mov"])
+                        .pick_file()
+                {
+                    self.import_media(path);
+                }
+                if ui.add_enabled(self.card_copy.is_none(), egui::Button::new("Import from Camera Card…")).clicked()
+                    && let Some(root) = FileDialog::new().set_title("Camera card").pick_folder()
+                {
+                    let clips = card::scan(&root);
+                    if clips.is_empty() {
+                        self.set_status("No camera clips found (looked for DCIM, AVCHD, XAVC and XDCAM folders).");
+                    } else {
+                        self.card_import = Some(clips.into_iter().map(|clip| (clip, true)).collect());
+                    }
+                }
+
+                if ui.button("Open").clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("Project", &["vproj"])
+                        .pick_file()
+                {
+                    self.open_project(ctx, path, false);
+                }
+                if ui.button("Open Read-only").on_hover_text("Open as a copy; the original is never overwritten").clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("Project", &["vproj"])
+                        .pick_file()
+                {
+                    self.open_project(ctx, path, true);
+                }
+
+                let mut recent_to_open = None;
+                ui.menu_button("Open Recent", |ui| {
+                    if self.recent_projects.is_empty() {
+                        ui.label("No recent projects");
+                    }
+                    for recent in &self.recent_projects {
+                        let name = recent.path.file_stem().unwrap_or_default().to_string_lossy();
+                        let summary = match &recent.info {
+                            Some(info) => format!("{} · {} clip(s)", format_timecode(info.duration_ms), info.clip_count),
+                            None => "missing".to_string(),
+                        };
+
+                        let clicked = ui.horizontal(|ui| {
+                            match &recent.poster {
+                                Some(poster) => { ui.add(egui::Image::new((poster.id(), egui::vec2(80.0, 45.0)))); }
+                                None => { ui.allocate_exact_size(egui::vec2(80.0, 45.0), egui::Sense::hover()); }
+                            }
+                            ui.add_enabled(recent.info.is_some(), egui::Button::new(format!("{}\n{}", name, summary)))
+                                .on_hover_text(recent.path.display().to_string())
+                                .clicked()
+                        }).inner;
+
+                        if clicked {
+                            recent_to_open = Some(recent.path.clone());
+                            ui.close();
+                        }
+                    }
+                });
+                if let Some(path) = recent_to_open {
+                    self.open_project(ctx, path, false);
+                }
+
+                if !self.clips.is_empty() {
+                    let save_res = ui.add_enabled(!self.project_read_only, egui::Button::new("Save"))
+                        .on_disabled_hover_text("Project is read-only, use Save As to take ownership");
+                    if save_res.clicked() {
+                        let path = self.project_path.clone().or_else(|| FileDialog::new()
+                            .add_filter("Project", &["vproj"])
+                            .save_file());
+                        if let Some(path) = path {
+                            self.save_project(ctx, path);
+                        }
+                    }
+                    if ui.button("Save As").clicked()
+                        && let Some(path) = FileDialog::new()
+                            .add_filter("Project", &["vproj"])
+                            .save_file()
+                    {
+                        self.save_project(ctx, path);
+                    }
+                    if self.project_read_only {
+                        ui.label("🔒 Read-only");
+                    }
+                    if ui.button("Compare…").on_hover_text("Show what changed against a saved version or backup of the project").clicked() {
+                        let mut dialog = FileDialog::new().add_filter("Project", &["vproj"]);
+                        if let Some(dir) = self.project_path.as_ref().and_then(|p| p.parent()) {
+                            dialog = dialog.set_directory(dir);
+                        }
+                        if let Some(path) = dialog.pick_file() {
+                            match project::load(&path) {
+                                Ok(saved) => self.compare_with = Some((path, saved.clips)),
+                                Err(err) => self.set_status(err),
+                            }
+                        }
+                    }
+
+                    if ui.add_enabled(!self.is_exporting, egui::Button::new("Export All")).clicked()
+                        && let Some(output) = FileDialog::new()
+                            .add_filter("MP4", &["mp4"])
+                            .save_file()
+                    {
+                        self.export_sequence(ctx, output);
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.media_bin.clear();
+                        self.clips.clear();
+                        self.trim_point = None;
+                        self.trim_frames = None;
+                        self.markers.clear();
+                        self.audio_tracks = AudioTracks::default();
+                        self.outro = outro::OutroTemplate::default();
+                        self.poster_frame = None;
+                        // self.clips.clear();
+                        self.playhead = 0;
+                        self.video_player.send_command(PlayerCommand::StopPlayback);
+                        self.is_playing = false;
+                    }
+                }
+
+                ui.separator();
+
+                if ui.button(if self.is_playing { "⏸ Pause" } else { "▶ Play" }).clicked() {
+                    self.is_playing = !self.is_playing;
+                    self.last_play_update_time = Instant::now();
+
+                    let active_clip_idx = self.clips.iter().position(|c| {
+                        let clip_timeline_end = c.timeline_start + (c.trim_end - c.trim_start);
+                        self.playhead >= c.timeline_start && self.playhead < clip_timeline_end
+                    });
+
+                    if let Some(idx) = active_clip_idx
+                        && self.clips[idx].generator.is_none()
+                    {
+                        if self.is_playing {
+                            let path = self.preview_path(idx);
+                            let active_clip = &self.clips[idx];
+                            let clip_playhead_offset_ms = self.playhead - active_clip.timeline_start;
+                            
+                            // very unoptimized (temp)
+                            self.video_player.send_command(PlayerCommand::LoadClip {
+                                path,
+                                trim_start_ms: active_clip.trim_start,
+                                trim_end_ms: active_clip.trim_end,
+                                filters: active_clip.preview_filters(),
+                            });
+
+                            self.video_player.send_command(PlayerCommand::StartPlayback { 
+                                timestamp_ms: clip_playhead_offset_ms 
+                            });
+                        } else {
+                            self.video_player.send_command(PlayerCommand::StopPlayback);
+                        }
+                    }
+                    
+                    ctx.request_repaint();
+                }
+
+                if ui.button("⏪ 5s").clicked() {
+                    self.playhead = self.playhead.saturating_sub(5000);
+                    self.last_play_update_time = Instant::now();
+                    self.last_requested_playhead_ms = u32::MAX;
+                    
+                    // Stop playback if currently playing
+                    if self.is_playing {
+                        self.is_playing = false;
+                        self.video_player.send_command(PlayerCommand::StopPlayback);
+                    }
+                    
+                    ctx.request_repaint();
+                }
+                if ui.button("⏩ 5s").clicked() {
+                    self.playhead = (self.playhead + 5000).min(self.total_timeline_duration);
+                    self.last_play_update_time = Instant::now();
+                    self.last_requested_playhead_ms = u32::MAX;
+                    
+                    // Stop playback if currently playing
+                    if self.is_playing {
+                        self.is_playing = false;
+                        self.video_player.send_command(PlayerCommand::StopPlayback);
+                    }
+                    
+                    ctx.request_repaint();
+                }
+
+                ui.separator();
+
+                if ui.add_enabled(!self.is_exporting, egui::Button::new("Re-render from Manifest…")).clicked()
+                    && let Some(manifest_path) = FileDialog::new()
+                        .add_filter("Manifest", &["json"])
+                        .pick_file()
+                {
+                    self.rerender_from_manifest(ctx, &manifest_path);
+                }
+
+                if ui.add_enabled(!self.clips.is_empty(), egui::Button::new("Copy Cut List")).clicked() {
+                    ctx.copy_text(self.timeline_summary());
+                    self.set_status("Cut list copied to clipboard.");
+                }
+
+                if ui.button("Settings").clicked() {
+                    self.settings_window = Some(settings::load());
+                }
+
+                if ui.button("Diagnostics").clicked() {
+                    let cache_dir = settings::cache_dir();
+                    let _ = std::fs::create_dir_all(&cache_dir);
+                    self.diagnostics_report = Some(diagnostics::capability_report(&cache_dir));
+                }
+
+                if ui.toggle_value(&mut self.low_power, "🔋 Battery Saver")
+                    .on_hover_text("Half-resolution, lower frame rate preview and paused background jobs")
+                    .changed()
+                {
+                    self.video_player.send_command(PlayerCommand::SetLowPower(self.low_power));
+                    // reload so the next frame is decoded at the new size
+                    self.current_active_clip_id = None;
+                }
+
+                if ui.toggle_value(&mut self.use_proxies, "Use Proxies")
+                    .on_hover_text("Preview from low-resolution proxies where they exist; export always uses the originals")
+                    .changed()
+                {
+                    self.current_active_clip_id = None;
+                }
+
+                ui.separator();
+
+                if ui.button("Add Marker").clicked() {
+                    let idx = self.markers.partition_point(|m| m.time <= self.playhead);
+                    self.markers.insert(idx, Marker { time: self.playhead, note: String::new() });
+                }
+            });
+
+            ui.separator();
+
+            // move playhead through time
+            if self.is_playing {
+                let elapsed_ms = self.last_play_update_time.elapsed().as_millis() as u32;
+                if elapsed_ms > 0 {
+                    self.playhead = (self.playhead + elapsed_ms).min(self.total_timeline_duration);
+                    self.last_play_update_time = Instant::now();
+                }   
+
+                // reached  end of timeline
+                if self.playhead >= self.total_timeline_duration {
+                    self.playhead = self.total_timeline_duration;
+                    self.is_playing = false;
+                    self.video_player.send_command(PlayerCommand::StopPlayback);
+                }
+            }
+
+            // preview display
+            // a detached preview leaves a thumbnail-sized copy here
+            let preview_scale = if self.preview_detached { 0.25 } else { 1.0 };
+            let preview_rect_size = egui::vec2(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32) * preview_scale;
+            let (preview_resp, painter) = ui.allocate_painter(
+                preview_rect_size,
+                egui::Sense::click_and_drag(),
+            );
+            painter.rect_filled(preview_resp.rect, 0.0, egui::Color32::from_black_alpha(200));
 
             if let Some(texture) = &self.current_preview_texture {
                 // have a frame
@@ -260,309 +1409,2109 @@ impl eframe::App for VideoEditorApp {
                 );
             }
 
-            // read new frame from thread
-            while let Ok(decoded_frame) = self.video_player.frame_receiver.try_recv() {
-                self.current_preview_texture = Some(ctx.load_texture(
-                    "video_preview_frame",
-                    decoded_frame.image,
-                    egui::TextureOptions::LINEAR,
-                ));
+            // two-up: outgoing last frame on the left, incoming first frame on the right
+            if self.trim_mode
+                && let Some(frames) = &self.trim_frames
+            {
+                let rect = preview_resp.rect;
+                ui.painter().rect_filled(rect, 0.0, egui::Color32::BLACK);
+                for (i, (frame, label)) in frames.iter().zip(["OUT", "IN"]).enumerate() {
+                    let half = egui::Rect::from_min_size(rect.min + egui::vec2(i as f32 * rect.width() / 2.0, 0.0), egui::vec2(rect.width() / 2.0, rect.height()));
+                    let image_rect = egui::Rect::from_center_size(half.center(), egui::vec2(half.width(), half.width() * 9.0 / 16.0));
+                    match frame {
+                        Some(texture) => { ui.painter().image(texture.id(), image_rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE); }
+                        None => { ui.painter().text(image_rect.center(), egui::Align2::CENTER_CENTER, "no clip", egui::FontId::proportional(14.0), egui::Color32::GRAY); }
+                    }
+                    ui.painter().text(image_rect.left_bottom() + egui::vec2(4.0, 16.0), egui::Align2::LEFT_TOP, label, egui::FontId::proportional(12.0), egui::Color32::WHITE);
+                }
+                ui.painter().vline(rect.center().x, rect.y_range(), egui::Stroke::new(1.0, egui::Color32::YELLOW));
             }
 
-            // if false && self.is_playing && self.pending_clip_transition {
-            //     self.pending_clip_transition = false;
-            //     
-            //     let current_idx = self.current_active_clip_id.unwrap_or(0);
-            //     
-            //     if let Some(next_clip) = self.clips.get(current_idx + 1) {
-            //         self.playhead = next_clip.timeline_start;
-            //         // TODO: handle gap betwen clips
-            //         self.video_player.send_command(PlayerCommand::LoadClip {
-            //             path: next_clip.path.clone(),
-            //             trim_start_ms: next_clip.trim_start,
-            //             trim_end_ms: next_clip.trim_end,
-            //         });
-            //         
-            //         self.video_player.send_command(PlayerCommand::StartPlayback {
-            //             timestamp_ms: 0,
-            //         });
-            //         
-            //         self.current_active_clip_id = Some(current_idx + 1);
-            //         self.last_requested_playhead_ms = 0;
-            //         ctx.request_repaint();
-            //     } else {
-            //         self.is_playing = false;
-            //         self.video_player.send_command(PlayerCommand::StopPlayback);
-            //     }
-            // }
+            if let Some((snapshot, snapshot_time)) = &self.compare_snapshot {
+                if preview_resp.dragged()
+                    && let Some(pos) = preview_resp.interact_pointer_pos()
+                {
+                    self.compare_wipe = ((pos.x - preview_resp.rect.left()) / preview_resp.rect.width()).clamp(0.0, 1.0);
+                }
+
+                let wipe_x = preview_resp.rect.left() + self.compare_wipe * preview_resp.rect.width();
+                ui.painter().image(
+                    snapshot.id(),
+                    egui::Rect::from_min_max(preview_resp.rect.min, egui::pos2(wipe_x, preview_resp.rect.max.y)),
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(self.compare_wipe, 1.0)),
+                    egui::Color32::WHITE,
+                );
+                ui.painter().vline(wipe_x, preview_resp.rect.y_range(), egui::Stroke::new(2.0, egui::Color32::WHITE));
+                ui.painter().text(preview_resp.rect.left_bottom() + egui::vec2(4.0, -4.0), egui::Align2::LEFT_BOTTOM, format!("A: {}", format_timecode(*snapshot_time)), egui::FontId::proportional(12.0), egui::Color32::WHITE);
+                ui.painter().text(preview_resp.rect.right_bottom() + egui::vec2(-4.0, -4.0), egui::Align2::RIGHT_BOTTOM, format!("B: {}", format_timecode(self.playhead)), egui::FontId::proportional(12.0), egui::Color32::WHITE);
+                if preview_resp.hovered() {
+                    ctx.set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                }
+            }
+
+            if let Some(loop_preview) = &mut self.loop_preview
+                && let Some(clip) = self.selected_clip.and_then(|idx| self.clips.get(idx)).filter(|c| c.generator.is_none())
+            {
+                loop_preview.update(ctx, clip);
+                let size = egui::vec2(preview_resp.rect.width() / 4.0, preview_resp.rect.width() / 4.0 * 9.0 / 16.0);
+                let rect = egui::Rect::from_min_size(preview_resp.rect.right_top() + egui::vec2(-size.x - 8.0, 8.0), size);
+                match loop_preview.current_frame() {
+                    Some(frame) => { ui.painter().image(frame.id(), rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE); }
+                    None => { ui.painter().rect_filled(rect, 0.0, egui::Color32::BLACK); }
+                }
+                ui.painter().rect_stroke(rect, 0.0, egui::Stroke::new(1.0, egui::Color32::WHITE), egui::StrokeKind::Outside);
+                if loop_preview.is_rendering() {
+                    ui.painter().text(rect.left_bottom() + egui::vec2(4.0, -4.0), egui::Align2::LEFT_BOTTOM, "↻", egui::FontId::proportional(12.0), egui::Color32::WHITE);
+                }
+                ctx.request_repaint_after(std::time::Duration::from_millis(50));
+            }
+
+            // stabilizer crop boundary for the clip under the playhead
+            if let Some(clip) = self.clips.iter().find(|c| self.playhead >= c.timeline_start && self.playhead < c.timeline_start + (c.trim_end - c.trim_start))
+                && let Some(zoom) = clip.stabilize_zoom
+            {
+                let inset = preview_resp.rect.size() * (zoom as f32 / 200.0);
+                let crop_rect = preview_resp.rect.shrink2(inset);
+                let dim = egui::Color32::from_black_alpha(120);
+                for outside in [
+                    egui::Rect::from_min_max(preview_resp.rect.min, egui::pos2(preview_resp.rect.max.x, crop_rect.min.y)),
+                    egui::Rect::from_min_max(egui::pos2(preview_resp.rect.min.x, crop_rect.max.y), preview_resp.rect.max),
+                    egui::Rect::from_min_max(egui::pos2(preview_resp.rect.min.x, crop_rect.min.y), egui::pos2(crop_rect.min.x, crop_rect.max.y)),
+                    egui::Rect::from_min_max(egui::pos2(crop_rect.max.x, crop_rect.min.y), egui::pos2(preview_resp.rect.max.x, crop_rect.max.y)),
+                ] {
+                    ui.painter().rect_filled(outside, 0.0, dim);
+                }
+                ui.painter().rect_stroke(crop_rect, 0.0, egui::Stroke::new(1.0, egui::Color32::YELLOW), egui::StrokeKind::Outside);
+                ui.painter().text(crop_rect.left_top() + egui::vec2(4.0, 4.0), egui::Align2::LEFT_TOP, format!("stabilizer crop {}%", zoom), egui::FontId::proportional(12.0), egui::Color32::YELLOW);
+            }
+
+            preview_resp.context_menu(|ui| {
+                if ui.button(if self.preview_detached { "Attach Preview" } else { "Detach Preview" }).clicked() {
+                    self.preview_detached = !self.preview_detached;
+                    ui.close();
+                }
+                ui.separator();
+                if ui.button(format!("Set Poster Frame at {}", format_timecode(self.playhead))).clicked() {
+                    self.poster_frame = Some(self.playhead);
+                    self.set_status("Poster frame set.");
+                    ui.close();
+                }
+                if self.poster_frame.is_some() && ui.button("Clear Poster Frame").clicked() {
+                    self.poster_frame = None;
+                    ui.close();
+                }
+                ui.separator();
+                if let Some(texture) = &self.current_preview_texture
+                    && ui.button("Snapshot for Compare").on_hover_text("Keep this frame on the left of a draggable wipe").clicked()
+                {
+                    self.compare_snapshot = Some((texture.clone(), self.playhead));
+                    ui.close();
+                }
+                if self.compare_snapshot.is_some() && ui.button("Clear Compare").clicked() {
+                    self.compare_snapshot = None;
+                    ui.close();
+                }
+                let label = if self.loop_preview.is_some() { "Hide Loop Preview" } else { "Loop Selected Clip" };
+                if ui.button(label).on_hover_text("Loop the selected clip's in/out range in the corner while you trim").clicked() {
+                    self.loop_preview = match self.loop_preview {
+                        Some(_) => None,
+                        None => Some(loop_preview::LoopPreview::new()),
+                    };
+                    ui.close();
+                }
+            });
+
+            while let Ok(result) = self.media_jobs.result_receiver.try_recv() {
+                match result {
+                    MediaJobResult::Waveform { path, waveform } => {
+                        self.waveforms.insert(path, waveform);
+                    }
+                    MediaJobResult::Thumbnail { path, image } => {
+                        let texture = image.map(|image| ctx.load_texture(format!("thumb_{}", path.display()), image, egui::TextureOptions::LINEAR));
+                        self.thumbnails.insert(path, texture);
+                    }
+                    MediaJobResult::CaptureDate { path, date } => {
+                        self.capture_dates.insert(path, date);
+                    }
+                    MediaJobResult::Proxy { path, proxy } => {
+                        self.proxies_pending = self.proxies_pending.saturating_sub(1);
+                        if proxy.is_none() {
+                            self.set_status(&format!("Could not make a proxy for {}", path.display()));
+                        }
+                        self.proxies.insert(path, proxy);
+                        if self.use_proxies {
+                            self.current_active_clip_id = None;
+                        }
+                    }
+                }
+            }
+
+            // read new frame from thread
+            while let Ok(decoded_frame) = self.video_player.frame_receiver.try_recv() {
+                self.current_preview_texture = Some(ctx.load_texture(
+                    "video_preview_frame",
+                    decoded_frame.image,
+                    egui::TextureOptions::LINEAR,
+                ));
+            }
+
+            // if false && self.is_playing && self.pending_clip_transition {
+            //     self.pending_clip_transition = false;
+            //     
+            //     let current_idx = self.current_active_clip_id.unwrap_or(0);
+            //     
+            //     if let Some(next_clip) = self.clips.get(current_idx + 1) {
+            //         self.playhead = next_clip.timeline_start;
+            //         // TODO: handle gap betwen clips
+            //         self.video_player.send_command(PlayerCommand::LoadClip {
+            //             path: next_clip.path.clone(),
+            //             trim_start_ms: next_clip.trim_start,
+            //             trim_end_ms: next_clip.trim_end,
+            //         });
+            //         
+            //         self.video_player.send_command(PlayerCommand::StartPlayback {
+            //             timestamp_ms: 0,
+            //         });
+            //         
+            //         self.current_active_clip_id = Some(current_idx + 1);
+            //         self.last_requested_playhead_ms = 0;
+            //         ctx.request_repaint();
+            //     } else {
+            //         self.is_playing = false;
+            //         self.video_player.send_command(PlayerCommand::StopPlayback);
+            //     }
+            // }
+
+            while self.video_player.playback_ended_receiver.try_recv().is_ok() {
+                if self.is_playing {
+                    self.pending_clip_transition = true;
+                    ctx.request_repaint();
+                }
+            }
+
+            // request new clip to load
+            const MIN_FRAME_REQUEST_INTERVAL_MS_SCRUBBING: u32 = 300;
+
+            let active_clip_idx = self.clips.iter().position(|c| {
+                let clip_timeline_end = c.timeline_start + (c.trim_end - c.trim_start);
+                self.playhead >= c.timeline_start && self.playhead < clip_timeline_end
+            });
+
+            if let Some(clip_idx) = active_clip_idx
+                && let Some([r, g, b]) = self.clips[clip_idx].generator
+            {
+                // generators have no media to decode, show the colour directly
+                if self.current_active_clip_id != Some(clip_idx) {
+                    self.current_active_clip_id = Some(clip_idx);
+                    self.video_player.send_command(PlayerCommand::StopPlayback);
+                    self.current_preview_texture = Some(ctx.load_texture(
+                        "video_preview_frame",
+                        egui::ColorImage::filled([PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize], egui::Color32::from_rgb(r, g, b)),
+                        egui::TextureOptions::LINEAR,
+                    ));
+                }
+            } else if let Some(clip_idx) = active_clip_idx {
+                let mut should_request_new_frame = false;
+
+                let active_clip = &self.clips[clip_idx];
+                let clip_playhead_offset_ms = self.playhead - active_clip.timeline_start;
+
+                if self.current_active_clip_id != Some(clip_idx) {
+                    // load new clip
+                    self.current_active_clip_id = Some(clip_idx);
+                    let path = self.preview_path(clip_idx);
+                    let active_clip = &self.clips[clip_idx];
+                    self.video_player.send_command(PlayerCommand::LoadClip {
+                        path,
+                        trim_start_ms: active_clip.trim_start,
+                        trim_end_ms: active_clip.trim_end,
+                        filters: active_clip.preview_filters(),
+                    });
+                    should_request_new_frame = true;
+                    self.last_requested_playhead_ms = u32::MAX;
+
+                    if self.is_playing {
+                        self.video_player.send_command(PlayerCommand::StartPlayback {
+                            timestamp_ms: clip_playhead_offset_ms,
+                        });
+                    }
+                }
+
+                if !self.is_playing { // scrubbing
+                    let time_since_last_request = self.last_playhead_update_time.elapsed().as_millis() as u32;
+
+                    if should_request_new_frame ||
+                        (clip_playhead_offset_ms != self.last_requested_playhead_ms &&
+                        time_since_last_request >= MIN_FRAME_REQUEST_INTERVAL_MS_SCRUBBING) {
+                        
+                        self.video_player.send_command(PlayerCommand::Seek {
+                            timestamp_ms: clip_playhead_offset_ms,
+                        });
+                        self.last_requested_playhead_ms = clip_playhead_offset_ms;
+                        self.last_playhead_update_time = Instant::now();
+                    }
+                }
+            } else {
+                self.current_preview_texture = Some(ctx.load_texture(
+                    "video_preview_frame",
+                    egui::ColorImage::filled([PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize], egui::Color32::BLACK),
+                    egui::TextureOptions::LINEAR,
+                ));
+            }
+
+            if self.is_playing {
+                if self.low_power {
+                    ctx.request_repaint_after(std::time::Duration::from_millis(66));
+                } else {
+                    ctx.request_repaint();
+                }
+            }
+
+            ui.add_space(30.0);
+
+            // timeline
+            ui.horizontal(|ui| {
+                ui.label("Timeline");
+                if ui.small_button("−").on_hover_text("Zoom out").clicked() {
+                    self.zoom_timeline(2.0, self.playhead);
+                }
+                if ui.small_button("+").on_hover_text("Zoom in").clicked() {
+                    self.zoom_timeline(0.5, self.playhead);
+                }
+                ui.checkbox(&mut self.auto_zoom_on_drag, "Zoom out while edge-scrolling")
+                    .on_hover_text("Hold Ctrl while dragging a clip to ripple-move it instead");
+                if ui.toggle_value(&mut self.trim_mode, "✂ Trim")
+                    .on_hover_text("Pick a clip edge, then , and . nudge it by one frame (Shift: 10 frames)")
+                    .clicked()
+                    && self.trim_mode
+                {
+                    self.refresh_trim_frames(ctx);
+                }
+                ui.separator();
+                ui.toggle_value(&mut self.video_track.show_thumbnails, "Thumbnails");
+                ui.toggle_value(&mut self.video_track.show_waveform, "Waveform");
+            });
+            if self.trim_mode
+                && self.trim_point.is_some()
+                && !ctx.wants_keyboard_input()
+            {
+                let nudge = ctx.input_mut(|i| {
+                    [(egui::Modifiers::NONE, egui::Key::Comma, -1), (egui::Modifiers::NONE, egui::Key::Period, 1),
+                     (egui::Modifiers::SHIFT, egui::Key::Comma, -10), (egui::Modifiers::SHIFT, egui::Key::Period, 10)]
+                        .into_iter()
+                        .filter(|(modifiers, key, _)| i.consume_key(*modifiers, *key))
+                        .map(|(_, _, frames)| frames)
+                        .sum::<i32>()
+                });
+                if nudge != 0 {
+                    self.nudge_trim_point(nudge);
+                    self.refresh_trim_frames(ctx);
+                }
+            }
+
+            if !ctx.wants_keyboard_input() && !self.media_bin.is_empty() {
+                let (up, down, place) = ctx.input_mut(|i| (
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::F9),
+                ));
+                let current = self.media_bin.iter().position(|m| Some(&m.path) == self.selected_bin_item.as_ref());
+                let next = match (current, up, down) {
+                    (Some(i), true, _) => Some(i.saturating_sub(1)),
+                    (Some(i), _, true) => Some((i + 1).min(self.media_bin.len() - 1)),
+                    (None, true, _) | (None, _, true) => Some(0),
+                    _ => current,
+                };
+                self.selected_bin_item = next.map(|i| self.media_bin[i].path.clone());
+                if place
+                    && let Some(i) = next
+                {
+                    let item = self.media_bin[i].clone();
+                    self.place_at_playhead(&item, self.overwrite_mode);
+                }
+            }
+
+            let timeline_height = self.video_track.height;
+            let (timeline_rect, _resp) = ui.allocate_at_least(egui::vec2(ui.available_width(), timeline_height), egui::Sense::hover());
+
+            // drag the bottom border to resize the track
+            let resize_rect = egui::Rect::from_x_y_ranges(timeline_rect.x_range(), timeline_rect.bottom()..=timeline_rect.bottom() + 5.0);
+            let resize_res = ui.interact(resize_rect, egui::Id::new("track_resize"), egui::Sense::drag());
+            if resize_res.hovered() || resize_res.dragged() {
+                ctx.set_cursor_icon(egui::CursorIcon::ResizeVertical);
+            }
+            if resize_res.dragged() {
+                self.video_track.height = (self.video_track.height + resize_res.drag_delta().y).clamp(30.0, 300.0);
+            }
+            let timeline_painter = ui.painter().with_clip_rect(egui::Rect::from_x_y_ranges(timeline_rect.x_range(), ui.clip_rect().y_range()));
+            timeline_painter.rect_filled(timeline_rect, 4.0, egui::Color32::from_gray(40));
+
+            let view_start = self.timeline_view_start;
+            let view_len = self.timeline_view_len;
+            let time_to_x = |t: u32| timeline_rect.left() + ((t as f32 - view_start as f32) / view_len as f32) * timeline_rect.width();
+            let x_to_time = |x: f32| (view_start as f32 + ((x - timeline_rect.left()) / timeline_rect.width()) * view_len as f32).max(0.0).round() as u32;
+
+            // wheel scrolls, ctrl+wheel zooms around the pointer
+            if let Some(pointer) = ctx.input(|i| i.pointer.hover_pos())
+                && timeline_rect.expand2(egui::vec2(0.0, 20.0)).contains(pointer)
+            {
+                let (scroll, zoom) = ctx.input(|i| (i.smooth_scroll_delta, i.zoom_delta()));
+                if zoom != 1.0 {
+                    self.zoom_timeline(1.0 / zoom, x_to_time(pointer.x));
+                } else if scroll != egui::Vec2::ZERO {
+                    let delta = scroll.x + scroll.y;
+                    self.scroll_timeline(-(delta / timeline_rect.width()) * view_len as f32);
+                }
+            }
+
+            let mut clip_to_update = None;
+            let mut refresh_trim_frames = false;
+            let mut resync_clip = None;
+            let ripple_held = ctx.input(|i| i.modifiers.command);
+            let mut ripple_drop: Option<(usize, Option<usize>, bool)> = None; // clip, insert before, released
+            let mut drag_readout: Option<(f32, String)> = None;
+
+            for (idx, clip) in self.clips.iter().enumerate() {
+                let is_selected = self.selected_clip == Some(idx);
+                let clip_duration = clip.trim_end - clip.trim_start;
+
+                let start_x = time_to_x(clip.timeline_start);
+                let end_x = time_to_x(clip.timeline_start + clip_duration);
+                
+                let clip_rect = egui::Rect::from_x_y_ranges(start_x..=end_x, timeline_rect.top()..=timeline_rect.bottom());
+                timeline_painter.rect_filled(clip_rect, 2.0, if is_selected { egui::Color32::from_rgb(60, 60, 200) } else { egui::Color32::from_rgb(60, 120, 180) });
+
+                let content_painter = timeline_painter.with_clip_rect(timeline_painter.clip_rect().intersect(clip_rect));
+                if self.video_track.show_thumbnails && clip.generator.is_none() {
+                    match self.thumbnails.get(&clip.path) {
+                        Some(Some(texture)) => {
+                            let thumb_rect = egui::Rect::from_min_size(clip_rect.min, egui::vec2(clip_rect.height() * 16.0 / 9.0, clip_rect.height()));
+                            content_painter.image(texture.id(), thumb_rect, egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::from_white_alpha(180));
+                        }
+                        Some(None) => {}
+                        None => {
+                            self.thumbnails.insert(clip.path.clone(), None);
+                            self.media_jobs.send_job(MediaJob::Thumbnail { path: clip.path.clone(), source_ms: clip.trim_start });
+                        }
+                    }
+                }
+                if self.video_track.show_waveform && clip.generator.is_none() {
+                    match self.waveforms.get(&clip.path) {
+                        Some(Some(waveform)) => {
+                            let mid_y = clip_rect.center().y + clip_rect.height() / 4.0;
+                            let half_h = clip_rect.height() / 4.0;
+                            let visible = clip_rect.intersect(timeline_rect);
+                            // fractional ms: zoomed in, a pixel is less than a millisecond
+                            let source_ms = |x: f32| {
+                                let time = view_start as f32 + (x - timeline_rect.left()) / timeline_rect.width() * view_len as f32;
+                                clip.trim_start as f32 + (time - clip.timeline_start as f32).max(0.0)
+                            };
+                            let mut x = visible.left();
+                            while x < visible.right() {
+                                let peak = waveform.peak(source_ms(x), source_ms(x + 1.0));
+                                let h = peak as f32 / 255.0 * half_h;
+                                content_painter.vline(x, (mid_y - h)..=(mid_y + h), egui::Stroke::new(1.0, egui::Color32::from_rgb(180, 230, 180)));
+                                x += 1.0;
+                            }
+                        }
+                        Some(None) => {}
+                        None => {
+                            self.waveforms.insert(clip.path.clone(), None);
+                            self.media_jobs.send_job(MediaJob::Waveform(clip.path.clone()));
+                        }
+                    }
+                }
+
+                // a replaced source can end before the clip's out point
+                let outline = if clip.generator.is_none() && clip.trim_end > clip.duration {
+                    egui::Stroke::new(2.0, egui::Color32::RED)
+                } else {
+                    egui::Stroke::new(1.0, egui::Color32::WHITE)
+                };
+                timeline_painter.rect_stroke(clip_rect, 2.0, outline, egui::StrokeKind::Inside);
+
+                let handle_w = 10.0;
+
+                let middle_drag_rect = egui::Rect::from_x_y_ranges(
+                    (start_x + handle_w)..=(end_x - handle_w),
+                    timeline_rect.top()..=timeline_rect.bottom(),
+                );
+                let l_handle = egui::Rect::from_x_y_ranges(start_x..=(start_x + handle_w), timeline_rect.top()..=timeline_rect.bottom());
+                let r_handle = egui::Rect::from_x_y_ranges((end_x - handle_w)..=end_x, timeline_rect.top()..=timeline_rect.bottom());
+
+                let l_res = ui.interact(l_handle.intersect(timeline_rect), egui::Id::new((idx, "l")), egui::Sense::drag());
+                let r_res = ui.interact(r_handle.intersect(timeline_rect), egui::Id::new((idx, "r")), egui::Sense::drag());
+
+                let middle_res = ui.interact(middle_drag_rect.intersect(timeline_rect), egui::Id::new((idx, "middle")), egui::Sense::drag());
+
+                if l_res.hovered() || r_res.hovered() || l_res.dragged() || r_res.dragged() {
+                    ctx.set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                } else if middle_res.hovered() || middle_res.dragged() {
+                    ctx.set_cursor_icon(if middle_res.dragged() {
+                        egui::CursorIcon::Grabbing
+                    } else {
+                        egui::CursorIcon::Grab
+                    });
+                }
+
+                if l_res.drag_started() || r_res.drag_started() || middle_res.drag_started() {
+                    self.drag_origin = Some((clip.timeline_start, clip.trim_start, clip.trim_end));
+                }
+                if l_res.drag_started() {
+                    self.trim_point = Some((idx, TrimSide::In));
+                }
+                if r_res.drag_started() {
+                    self.trim_point = Some((idx, TrimSide::Out));
+                }
+                if l_res.drag_stopped() || r_res.drag_stopped() || middle_res.drag_stopped() {
+                    self.drag_origin = None;
+                }
+                if l_res.drag_stopped() || r_res.drag_stopped() {
+                    refresh_trim_frames = true;
+                }
+                let (origin_start, origin_in, origin_out) = self.drag_origin.unwrap_or((clip.timeline_start, clip.trim_start, clip.trim_end));
+
+                if l_res.dragged() {
+                    let timeline_end = clip.timeline_start + clip.trim_end - clip.trim_start;
+                    let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
+                    let new_timeline_start = x_to_time(pointer_x)
+                        .clamp(0, self.total_timeline_duration - MIN_CLIP_DURATION)
+                        .clamp(clip.timeline_start - clip.trim_start, timeline_end - MIN_CLIP_DURATION);
+
+                    let new_trim_start = clip.trim_end - (timeline_end - new_timeline_start);
+                    
+                    clip_to_update = Some((idx, new_timeline_start, new_trim_start, clip.trim_end));
+                    drag_readout = Some((start_x, format!("In {} ({})", format_precise_timecode(new_trim_start), format_delta(origin_in, new_trim_start))));
+                }
+                if r_res.dragged() {
+                    let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
+                    let new_timeline_end = x_to_time(pointer_x)
+                        .clamp(clip.timeline_start + MIN_CLIP_DURATION, self.total_timeline_duration);
+                    // a source that got shorter (see Refresh Metadata) can end before the minimum
+                    let min_trim_end = clip.trim_start + MIN_CLIP_DURATION;
+                    let new_trim_end = (clip.trim_start + (new_timeline_end - clip.timeline_start))
+                        .clamp(min_trim_end, clip.duration.max(min_trim_end));
+                    clip_to_update = Some((idx, clip.timeline_start, clip.trim_start, new_trim_end));
+                    drag_readout = Some((end_x, format!("Out {} ({})", format_precise_timecode(new_trim_end), format_delta(origin_out, new_trim_end))));
+                }
+                
+                if middle_res.drag_started() {
+                    println!("dragstart");
+                    self.selected_gap = None;
+                    let pointer_pos = ctx.input(|i| i.pointer.press_origin()).unwrap_or_default();
+                    self.clip_drag_init = x_to_time(pointer_pos.x).saturating_sub(clip.timeline_start);
+                    self.selected_clip = Some(idx);
+                }
+
+                if (middle_res.dragged() || middle_res.drag_stopped()) && ripple_held {
+                    let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
+                    let before = self.ripple_target(x_to_time(pointer_x), idx);
+                    ripple_drop = Some((idx, before, middle_res.drag_stopped()));
+                }
+
+                if middle_res.dragged() && !ripple_held {
+                    let current_pos = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default());
+
+                    let prev = self.clips.iter()
+                        .map(|c| { c.timeline_start + c.trim_end - c.trim_start })
+                        .filter(|timeline_end| { *timeline_end <= clip.timeline_start })
+                        .max()
+                        .unwrap_or(0);
+
+                    let timeline_end = clip.timeline_start + clip.trim_end - clip.trim_start;
+
+                    let next = self.clips.iter()
+                        .map(|c| { c.timeline_start })
+                        .filter(|timeline_start| { *timeline_start >= timeline_end })
+                        .min()
+                        .unwrap_or(self.total_timeline_duration)
+                        .saturating_sub(clip_duration);
+
+                    // time-based so the clip keeps following the pointer while the view edge-scrolls
+                    let new_timeline_start = x_to_time(current_pos.x)
+                        .saturating_sub(self.clip_drag_init)
+                        .clamp(prev, next.max(prev));
+                    
+                    clip_to_update = Some((idx, new_timeline_start, clip.trim_start, clip.trim_end));
+                    drag_readout = Some((start_x, format!("Start {} ({})", format_precise_timecode(new_timeline_start), format_delta(origin_start, new_timeline_start))));
+                }
+
+                if middle_res.drag_stopped() {
+                    self.clip_drag_init = 0;
+                }
+
+                timeline_painter.rect_filled(l_handle, 2.0, egui::Color32::LIGHT_GREEN);
+                timeline_painter.rect_filled(r_handle, 2.0, egui::Color32::LIGHT_GREEN);
+                if self.trim_mode
+                    && let Some((i, side)) = self.trim_point
+                    && i == idx
+                {
+                    let handle = if side == TrimSide::In { l_handle } else { r_handle };
+                    timeline_painter.rect_filled(handle, 2.0, egui::Color32::YELLOW);
+                }
+
+                let capture_date = match self.capture_dates.get(&clip.path) {
+                    Some(date) => date.as_deref(),
+                    None => {
+                        if self.clip_label.contains("{date}") && clip.generator.is_none() {
+                            self.capture_dates.insert(clip.path.clone(), None);
+                            self.media_jobs.send_job(MediaJob::CaptureDate(clip.path.clone()));
+                        }
+                        None
+                    }
+                };
+                let label = clip.label(&self.clip_label, capture_date);
+                timeline_painter.text(clip_rect.left_top() + egui::vec2(5.0, 15.0), egui::Align2::LEFT_TOP, label, egui::FontId::proportional(12.0), egui::Color32::WHITE);
+
+                let has_proxy = clip.generator.is_none() && self.proxies.entry(clip.path.clone())
+                    .or_insert_with(|| media_jobs::proxy_file(&clip.path).filter(|file| file.exists()))
+                    .is_some();
+                if has_proxy {
+                    let in_use = self.use_proxies && clip.crop.is_none();
+                    let galley = timeline_painter.layout_no_wrap("P".to_string(), egui::FontId::monospace(10.0), egui::Color32::WHITE);
+                    let badge = egui::Rect::from_min_size(clip_rect.left_bottom() + egui::vec2(handle_w + 2.0, -galley.size().y - 4.0), galley.size() + egui::vec2(4.0, 2.0));
+                    let fill = if in_use { egui::Color32::from_rgb(40, 140, 60) } else { egui::Color32::from_gray(90) };
+                    timeline_painter.rect_filled(badge, 2.0, fill);
+                    timeline_painter.galley(badge.min + egui::vec2(2.0, 1.0), galley, egui::Color32::WHITE);
+                    ui.interact(badge.intersect(timeline_rect), egui::Id::new((idx, "proxy")), egui::Sense::hover())
+                        .on_hover_text(if in_use { "Previewing from proxy" } else { "Proxy available" });
+                }
+
+                if clip.audio_offset != 0 {
+                    let galley = timeline_painter.layout_no_wrap(format!("{:+}f", clip.audio_offset_frames()), egui::FontId::monospace(10.0), egui::Color32::WHITE);
+                    let badge = egui::Rect::from_min_size(clip_rect.right_top() + egui::vec2(-handle_w - galley.size().x - 6.0, 2.0), galley.size() + egui::vec2(4.0, 2.0));
+                    timeline_painter.rect_filled(badge, 2.0, egui::Color32::from_rgb(200, 30, 30));
+                    timeline_painter.galley(badge.min + egui::vec2(2.0, 1.0), galley, egui::Color32::WHITE);
+                    let badge_res = ui.interact(badge.intersect(timeline_rect), egui::Id::new((idx, "sync")), egui::Sense::click())
+                        .on_hover_text("Audio out of sync with its video. Click to resync.");
+                    if badge_res.clicked() {
+                        resync_clip = Some(idx);
+                    }
+                }
+            }
+
+            // edge-scroll while a clip or trim handle is being dragged
+            if self.drag_origin.is_some()
+                && let Some(pointer) = ctx.input(|i| i.pointer.latest_pos())
+            {
+                const EDGE_WIDTH: f32 = 40.0;
+                let into_left = (timeline_rect.left() + EDGE_WIDTH - pointer.x) / EDGE_WIDTH;
+                let into_right = (pointer.x - (timeline_rect.right() - EDGE_WIDTH)) / EDGE_WIDTH;
+                let push = if into_left > 0.0 { -into_left.min(2.0) } else if into_right > 0.0 { into_right.min(2.0) } else { 0.0 };
+
+                if push != 0.0 {
+                    // up to 2% of the visible span per frame, faster the further past the edge
+                    self.scroll_timeline(push * 0.02 * view_len as f32);
+                    if self.auto_zoom_on_drag {
+                        self.zoom_before_drag.get_or_insert(view_len);
+                        let anchor = if push < 0.0 { view_start + view_len } else { view_start };
+                        self.zoom_timeline(1.01, anchor);
+                    }
+                    ctx.request_repaint();
+                }
+            } else if let Some(len) = self.zoom_before_drag.take() {
+                let centre = self.timeline_view_start + self.timeline_view_len / 2;
+                self.timeline_view_len = len;
+                self.timeline_view_start = centre.saturating_sub(len / 2);
+                self.scroll_timeline(0.0);
+            }
+
+            if let Some((gap_start, gap_end)) = self.selected_gap {
+                let gap_rect = egui::Rect::from_x_y_ranges(time_to_x(gap_start)..=time_to_x(gap_end), timeline_rect.y_range());
+                timeline_painter.rect_filled(gap_rect, 2.0, egui::Color32::from_rgba_unmultiplied(255, 160, 0, 40));
+                timeline_painter.rect_stroke(gap_rect, 2.0, egui::Stroke::new(1.0, egui::Color32::ORANGE), egui::StrokeKind::Inside);
+            }
+
+            if let Some((x, text)) = drag_readout {
+                let galley = timeline_painter.layout_no_wrap(text, egui::FontId::monospace(12.0), egui::Color32::WHITE);
+                let label_rect = egui::Rect::from_min_size(
+                    egui::pos2(x - galley.size().x / 2.0, timeline_rect.bottom() + 4.0),
+                    galley.size(),
+                ).expand(3.0);
+                timeline_painter.rect_filled(label_rect, 3.0, egui::Color32::from_black_alpha(220));
+                timeline_painter.galley(label_rect.min + egui::vec2(3.0, 3.0), galley, egui::Color32::WHITE);
+            }
+
+            if let Some((idx, new_timeline_start, new_start, new_end)) = clip_to_update {
+                let clip = &self.clips[idx];
+                // moving the out point of the playing clip ahead of the playhead is absorbed by the
+                // player; any other edit stops playback
+                let live_out_trim = self.is_playing
+                    && self.current_active_clip_id == Some(idx)
+                    && (new_timeline_start, new_start) == (clip.timeline_start, clip.trim_start)
+                    && new_timeline_start + (new_end - new_start) > self.playhead;
+                if live_out_trim {
+                    self.video_player.send_command(PlayerCommand::SetTrimEnd { trim_end_ms: new_end });
+                } else if self.is_playing {
+                    self.is_playing = false;
+                    self.video_player.send_command(PlayerCommand::StopPlayback);
+                }
+                
+                self.clips[idx].timeline_start = new_timeline_start;
+                self.clips[idx].trim_start = new_start;
+                self.clips[idx].trim_end = new_end;
+            }
+            match ripple_drop {
+                Some((idx, before, true)) => self.ripple_move(idx, before),
+                Some((idx, before, false)) => {
+                    let time = match before {
+                        Some(j) => self.clips[j].timeline_start,
+                        None => self.clips.iter().enumerate()
+                            .filter(|(i, _)| *i != idx)
+                            .map(|(_, c)| c.timeline_start + (c.trim_end - c.trim_start))
+                            .fold(0, u32::max),
+                    };
+                    let x = time_to_x(time);
+                    timeline_painter.vline(x, (timeline_rect.top() - 4.0)..=(timeline_rect.bottom() + 4.0), egui::Stroke::new(3.0, egui::Color32::from_rgb(80, 220, 255)));
+                }
+                None => {}
+            }
+
+            if let Some(idx) = resync_clip {
+                self.clips[idx].audio_offset = 0;
+                self.set_status("Audio resynced.");
+            }
+            if refresh_trim_frames && self.trim_mode {
+                self.refresh_trim_frames(ctx);
+            }
+
+            for marker in &self.markers {
+                let x = time_to_x(marker.time);
+                let y = timeline_rect.top() - 12.0;
+                timeline_painter.add(egui::Shape::convex_polygon(
+                    vec![egui::pos2(x - 5.0, y - 6.0), egui::pos2(x + 5.0, y - 6.0), egui::pos2(x, y + 4.0)],
+                    egui::Color32::YELLOW,
+                    egui::Stroke::NONE,
+                ));
+            }
+
+            if let Some(poster_time) = self.poster_frame {
+                let x = time_to_x(poster_time);
+                timeline_painter.text(egui::pos2(x, timeline_rect.top() - 2.0), egui::Align2::CENTER_BOTTOM, "P", egui::FontId::monospace(10.0), egui::Color32::LIGHT_BLUE);
+            }
+
+            let ph_x = time_to_x(self.playhead);
+
+            
+            let ph_rect = egui::Rect::from_x_y_ranges(ph_x-1.0..=ph_x+1.0, timeline_rect.top()-20.0..=timeline_rect.bottom());
+            timeline_painter.rect_filled(ph_rect, 2.0, egui::Color32::RED);
+
+            let ph_jump_rect = egui::Rect::from_min_max(egui::pos2(timeline_rect.min.x, timeline_rect.min.y - 20.0), timeline_rect.max);
+            
+            let ph_jump_res = ui.interact(ph_jump_rect, egui::Id::new("ph_jump"), egui::Sense::click_and_drag());
+
+            if ph_jump_res.clicked()
+                && let Some(pointer) = ph_jump_res.interact_pointer_pos()
+            {
+                let time = x_to_time(pointer.x);
+                self.selected_gap = if timeline_rect.contains(pointer) {
+                    self.timeline_gaps().into_iter().find(|(start, end)| time >= *start && time < *end)
+                } else {
+                    None
+                };
+                if self.selected_gap.is_some() {
+                    self.selected_clip = None;
+                }
+            }
+
+            if ph_jump_res.dragged() {
+                let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
+                self.playhead = x_to_time(pointer_x).min(self.total_timeline_duration);
+            }
+
+
+            // ui.painter().line_segment(
+            //     [egui::pos2(ph_x, rect.top() - 30.0), egui::pos2(ph_x, rect.bottom())],
+            //     egui::Stroke::new(3.0, egui::Color32::RED),
+            // );
+
+            ui.add_space(10.0);
+
+            if let Some((gap_start, gap_end)) = self.selected_gap {
+                let mut gap_action = None;
+                ui.group(|ui| {
+                    ui.label(format!("Gap: {} – {}", format_precise_timecode(gap_start), format_precise_timecode(gap_end)));
+                    ui.horizontal(|ui| {
+                        let mut length_secs = (gap_end - gap_start) as f32 / 1000.0;
+                        ui.label("Length");
+                        if ui.add(egui::DragValue::new(&mut length_secs).speed(0.05).range(0.0..=3600.0).suffix(" s")).changed() {
+                            gap_action = Some(GapAction::Resize((length_secs * 1000.0).round() as u32));
+                        }
+                        if ui.button("Ripple Delete").clicked() {
+                            gap_action = Some(GapAction::Resize(0));
+                        }
+                        if ui.button("Fill with Black").clicked() {
+                            gap_action = Some(GapAction::Fill);
+                        }
+                    });
+                });
+
+                match gap_action {
+                    Some(GapAction::Resize(new_len)) => self.resize_gap(gap_start, gap_end, new_len),
+                    Some(GapAction::Fill) => {
+                        // keep timeline order, export concatenates clips as listed
+                        let idx = self.clips.partition_point(|c| c.timeline_start < gap_start);
+                        self.clips.insert(idx, VideoClip {
+                            name: "Solid colour".to_string(),
+                            duration: 60 * 60 * 1000,
+                            timeline_start: gap_start,
+                            trim_start: 0,
+                            trim_end: gap_end - gap_start,
+                            generator: Some([0, 0, 0]),
+                            ..Default::default()
+                        });
+                        self.selected_gap = None;
+                        self.selected_clip = Some(idx);
+                        self.current_active_clip_id = None;
+                    }
+                    None => {}
+                }
+            }
+
+            let clips_for_matching = self.clips.clone();
+            let mut revert_clip = None;
+            let mut send_to_compositor = None;
+            if let Some(clip) = self.selected_clip.and_then(|idx| self.clips.get_mut(idx)) {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Clip: {}", clip.name));
+                        if clip.generator.is_none() {
+                            let used = clip.trim_end - clip.trim_start;
+                            ui.weak(format!(
+                                "using {} of {} ({}%)",
+                                format_timecode(used),
+                                format_timecode(clip.duration),
+                                used as u64 * 100 / clip.duration.max(1) as u64
+                            ));
+                        }
+                        if ui.small_button("Revert Clip").on_hover_text("Restore the full source and clear all effects").clicked() {
+                            revert_clip = self.selected_clip;
+                        }
+                        if clip.generator.is_none()
+                            && ui.small_button("Send to Compositor").on_hover_text("Write the trimmed segment losslessly and replace the clip when the composited render appears next to it").clicked()
+                        {
+                            send_to_compositor = self.selected_clip;
+                        }
+                    });
+                    if clip.generator.is_none() && clip.trim_end > clip.duration {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(egui::Color32::RED, format!("Out point is past the end of the source ({})", format_timecode(clip.duration)));
+                            if ui.small_button("Clamp").on_hover_text("Move the out point back to the end of the source").clicked() {
+                                clip.trim_end = clip.duration;
+                                clip.trim_start = clip.trim_start.min(clip.duration.saturating_sub(MIN_CLIP_DURATION));
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        let mut stabilize = clip.stabilize_zoom.is_some();
+                        if ui.checkbox(&mut stabilize, "Stabilize").changed() {
+                            clip.stabilize_zoom = stabilize.then_some(10);
+                        }
+                        if let Some(zoom) = &mut clip.stabilize_zoom {
+                            ui.add(egui::Slider::new(zoom, 1..=30).suffix("% crop"));
+                        }
+                    });
+                    let orientation_before = (clip.rotation, clip.hflip, clip.vflip);
+                    ui.horizontal(|ui| {
+                        if ui.button("⟲ 90°").clicked() {
+                            clip.rotation = (clip.rotation + 270) % 360;
+                        }
+                        if ui.button("⟳ 90°").clicked() {
+                            clip.rotation = (clip.rotation + 90) % 360;
+                        }
+                        ui.toggle_value(&mut clip.hflip, "Flip H");
+                        ui.toggle_value(&mut clip.vflip, "Flip V");
+                        if clip.rotation != 0 {
+                            ui.label(format!("{}°", clip.rotation));
+                        }
+                    });
+                    let mut reload_preview = orientation_before != (clip.rotation, clip.hflip, clip.vflip);
+
+                    if clip.generator.is_none() {
+                        ui.horizontal(|ui| {
+                            ui.label("Stem");
+                            for stem in Stem::ALL {
+                                ui.selectable_value(&mut clip.stem, stem, stem.label());
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Audio slip");
+                            let mut frames = clip.audio_offset_frames();
+                            if ui.add(egui::DragValue::new(&mut frames).range(-300..=300).suffix(" f")).changed() {
+                                clip.audio_offset = (frames as f32 * 1000.0 / TIMELINE_FPS).round() as i32;
+                            }
+                            if clip.audio_offset != 0 {
+                                ui.colored_label(egui::Color32::from_rgb(230, 60, 60), "out of sync");
+                                if ui.small_button("Resync").clicked() {
+                                    clip.audio_offset = 0;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(self.crop_detection.is_none(), egui::Button::new("Detect Black Bars")).clicked() {
+                                self.crop_detection = Some(letterbox::start(ctx, clip));
+                            }
+                            if self.crop_detection.is_some() {
+                                ui.add(egui::Spinner::new());
+                            }
+                            if let Some(crop) = clip.crop {
+                                ui.label(format!("Cropped to {}×{}", crop.width, crop.height));
+                                if ui.small_button("Remove Crop").clicked() {
+                                    clip.crop = None;
+                                    reload_preview = true;
+                                }
+                            }
+                        });
+                        if let Some((idx, crop)) = self.proposed_crop
+                            && Some(idx) == self.selected_clip
+                        {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("Bars found: crop to {}×{} at {},{}?", crop.width, crop.height, crop.x, crop.y));
+                                if ui.button("Apply Crop").clicked() {
+                                    clip.crop = Some(crop);
+                                    self.proposed_crop = None;
+                                    reload_preview = true;
+                                }
+                                if ui.button("Dismiss").clicked() {
+                                    self.proposed_crop = None;
+                                }
+                            });
+                        }
+                    }
+
+                    if let Some(rgb) = &mut clip.generator {
+                        ui.horizontal(|ui| {
+                            ui.label("Colour");
+                            reload_preview |= ui.color_edit_button_srgb(rgb).changed();
+                        });
+                    }
+
+                    ui.collapsing("Grade", |ui| {
+                        for (i, channel) in ["R", "G", "B"].iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let gain = ui.add(egui::Slider::new(&mut clip.grade.gain[i], 0.5..=2.0).text(format!("{} gain", channel)));
+                                let offset = ui.add(egui::Slider::new(&mut clip.grade.offset[i], -64.0..=64.0).text(format!("{} offset", channel)));
+                                for res in [gain, offset] {
+                                    reload_preview |= res.drag_stopped() || (res.changed() && !res.dragged());
+                                }
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Reset Grade").clicked() {
+                                clip.grade = ColorGrade::default();
+                                reload_preview = true;
+                            }
+                            ui.menu_button("Match to…", |ui| {
+                                for (other_idx, other) in clips_for_matching.iter().enumerate() {
+                                    if Some(other_idx) != self.selected_clip && ui.button(&other.name).clicked() {
+                                        self.shot_match = Some(shotmatch::start(ctx, other, clip));
+                                        self.status_message = format!("Sampling frames to match {}…", other.name);
+                                        ui.close();
+                                    }
+                                }
+                            });
+                        });
+                    });
+
+                    if reload_preview {
+                        // reload so the preview picks up the new filters
+                        self.current_active_clip_id = None;
+                    }
+                });
+            }
+            if let Some(idx) = revert_clip {
+                self.revert_clip(idx);
+            }
+            if let Some(idx) = send_to_compositor {
+                self.send_to_compositor(ctx, idx);
+            }
+
+            let mut refresh_metadata = false;
+            ui.collapsing(format!("Media Bin ({})", self.media_bin.len()), |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.bin_unused_only, "Unused only");
+                    if ui.add_enabled(self.media_move.is_none(), egui::Button::new("Remove Unused…")).clicked() {
+                        self.confirm_remove_unused = Some(false);
+                    }
+                    if ui.button("Refresh Metadata").on_hover_text("Probe the sources again, e.g. after a file was replaced with a different cut").clicked() {
+                        refresh_metadata = true;
+                    }
+                    ui.separator();
+                    ui.selectable_value(&mut self.overwrite_mode, false, "Insert");
+                    ui.selectable_value(&mut self.overwrite_mode, true, "Overwrite");
+                    ui.weak("F9 at playhead, ↑/↓ to pick");
+                });
+                let missing: Vec<PathBuf> = self.media_bin.iter()
+                    .filter(|item| self.proxies.entry(item.path.clone())
+                        .or_insert_with(|| media_jobs::proxy_file(&item.path).filter(|file| file.exists()))
+                        .is_none())
+                    .map(|item| item.path.clone())
+                    .collect();
+                ui.horizontal(|ui| {
+                    if self.proxies_pending > 0 {
+                        ui.spinner();
+                        ui.label(format!("Making proxies ({} left)", self.proxies_pending));
+                    } else if ui.add_enabled(!missing.is_empty(), egui::Button::new(format!("Make Proxies ({})", missing.len())))
+                        .on_hover_text("Low-resolution copies in the cache folder for smoother preview")
+                        .clicked()
+                    {
+                        self.proxies_pending = missing.len();
+                        for path in missing {
+                            self.media_jobs.send_job(MediaJob::Proxy(path));
+                        }
+                    }
+                });
+                let mut to_append = None;
+                for item in &self.media_bin {
+                    let uses = item.usage_count(&self.clips);
+                    if self.bin_unused_only && uses > 0 {
+                        continue;
+                    }
+                    ui.horizontal(|ui| {
+                        let selected = self.selected_bin_item.as_ref() == Some(&item.path);
+                        if ui.selectable_label(selected, &item.name).on_hover_text(item.path.display().to_string()).clicked() {
+                            self.selected_bin_item = Some(item.path.clone());
+                        }
+                        ui.weak(format_timecode(item.duration));
+                        match uses {
+                            0 => ui.colored_label(egui::Color32::GRAY, "unused"),
+                            n => ui.label(format!("used {}×", n)),
+                        };
+                        if item.changed_on_disk() {
+                            ui.colored_label(egui::Color32::ORANGE, "changed on disk")
+                                .on_hover_text("The file was replaced or modified since it was probed; use Refresh Metadata");
+                        }
+                        if ui.add_enabled(uses > 0, egui::Button::new("Jump").small())
+                            .on_hover_text("Select the next use after the playhead")
+                            .clicked()
+                        {
+                            let mut usages: Vec<(usize, u32)> = self.clips.iter().enumerate()
+                                .filter(|(_, c)| c.generator.is_none() && c.path == item.path)
+                                .map(|(idx, c)| (idx, c.timeline_start))
+                                .collect();
+                            usages.sort_by_key(|(_, start)| *start);
+                            let next = usages.iter().find(|(_, start)| *start > self.playhead).or(usages.first());
+                            if let Some(&(idx, start)) = next {
+                                self.selected_clip = Some(idx);
+                                self.selected_gap = None;
+                                self.playhead = start;
+                                let len = self.timeline_view_len;
+                                self.timeline_view_start = start.saturating_sub(len / 2);
+                            }
+                        }
+                        if ui.small_button("Add to Timeline").clicked() {
+                            to_append = Some(item.clone());
+                        }
+                    });
+                }
+                if let Some(item) = to_append {
+                    self.append_to_timeline(&item);
+                }
+            });
+
+            if refresh_metadata {
+                self.refresh_metadata();
+            }
+
+            ui.collapsing("Outro", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Title");
+                    ui.text_edit_singleline(&mut self.outro.title);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Background");
+                    ui.color_edit_button_srgb(&mut self.outro.background);
+                    ui.label("Logo");
+                    match &self.outro.logo {
+                        Some(logo) => {
+                            ui.label(logo.file_name().unwrap_or_default().to_string_lossy()).on_hover_text(logo.display().to_string());
+                            if ui.small_button("🗑").clicked() {
+                                self.outro.logo = None;
+                            }
+                        }
+                        None => {
+                            if ui.small_button("Choose…").clicked()
+                                && let Some(path) = FileDialog::new().add_filter("Image", &["png", "jpg", "jpeg"]).pick_file()
+                            {
+                                self.outro.logo = Some(path);
+                            }
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut length_secs = self.outro.duration_ms as f32 / 1000.0;
+                    if ui.add(egui::DragValue::new(&mut length_secs).speed(0.05).range(1.0..=30.0).prefix("Length ").suffix(" s")).changed() {
+                        self.outro.duration_ms = (length_secs * 1000.0).round() as u32;
+                    }
+                    let mut fade_secs = self.outro.fade_ms as f32 / 1000.0;
+                    if ui.add(egui::DragValue::new(&mut fade_secs).speed(0.05).range(0.0..=5.0).prefix("Fade ").suffix(" s")).changed() {
+                        self.outro.fade_ms = (fade_secs * 1000.0).round() as u32;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.outro_render.is_none(), egui::Button::new("Append Outro"))
+                        .on_hover_text("Renders the end card from the last frame and adds it to the end of the timeline")
+                        .clicked()
+                    {
+                        self.append_outro(ctx);
+                    }
+                    if self.outro_render.is_some() {
+                        ui.add(egui::Spinner::new());
+                    }
+                });
+            });
+
+            ui.collapsing("Export Settings", |ui| {
+                ui.checkbox(&mut self.write_manifest_on_export, "Write manifest (sources, hashes, trims, ffmpeg arguments)")
+                    .on_hover_text("Saved as <output>.manifest.json so the deliverable can be traced and re-rendered");
+
+                ui.checkbox(&mut self.notify_on_export, "Desktop notification when the export finishes");
+                ui.horizontal(|ui| {
+                    // keep at least one stream
+                    ui.add_enabled_ui(self.export_audio, |ui| ui.checkbox(&mut self.export_video, "Video"));
+                    ui.add_enabled_ui(self.export_video, |ui| ui.checkbox(&mut self.export_audio, "Audio"));
+                });
+                ui.add_enabled(self.export_audio, egui::Checkbox::new(&mut self.export_stems, "Also write audio stems (WAV)"))
+                    .on_hover_text("One full-length WAV per stem used (<output>.dialog.wav, .music.wav, .sfx.wav), set per clip in the inspector");
+                ui.horizontal(|ui| {
+                    ui.label("Channels");
+                    egui::ComboBox::from_id_salt("channel_layout")
+                        .selected_text(self.channel_layout.label())
+                        .show_ui(ui, |ui| {
+                            for layout in ChannelLayout::ALL {
+                                ui.selectable_value(&mut self.channel_layout, layout, layout.label());
+                            }
+                        });
+                })
+                .response
+                .on_hover_text("Sources with different layouts need converting for the clips to join cleanly");
+                ui.horizontal(|ui| {
+                    ui.label("Crossfade cuts");
+                    ui.add(egui::DragValue::new(&mut self.audio_tracks.cut_fade_frames).range(0..=12).suffix(" fr"));
+                })
+                .response
+                .on_hover_text("Short audio-only fade across every edit point to avoid clicks; 0 keeps hard cuts. Saved with the project.");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let mut pipeline_dialog = FileDialog::new().set_title("Pipeline file").add_filter("Pipeline", &["toml"]);
+                    if let Some(dir) = self.pipeline_file.as_ref().and_then(|f| f.parent()) {
+                        pipeline_dialog = pipeline_dialog.set_directory(dir);
+                    }
+                    if ui.add_enabled(!self.clips.is_empty() && !self.is_exporting, egui::Button::new("Run Pipeline…"))
+                        .on_hover_text("Render every output listed in a pipeline .toml file (e.g. master, 720p preview, audio-only) in one pass")
+                        .clicked()
+                        && let Some(pipeline_file) = pipeline_dialog.pick_file()
+                        && let Some(base) = FileDialog::new().set_title("Base name for the pipeline outputs").save_file()
+                    {
+                        self.export_pipeline(ctx, &pipeline_file, base);
+                        self.pipeline_file = Some(pipeline_file);
+                    }
+                    if let Some(file) = &self.pipeline_file {
+                        ui.weak(file.file_name().unwrap_or_default().to_string_lossy());
+                    }
+                });
+
+                ui.separator();
+                ui.label("Remote render (ssh)");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.remote_target.host).hint_text("user@host").desired_width(140.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.remote_target.work_dir).hint_text("remote work folder").desired_width(160.0));
+                    let ready = !self.remote_target.host.is_empty() && !self.remote_target.work_dir.is_empty() && !self.clips.is_empty();
+                    if ui.add_enabled(ready && self.remote_render.is_none(), egui::Button::new("Export Remotely…"))
+                        .on_hover_text("Uploads the sources, renders with the remote ffmpeg and downloads the result")
+                        .clicked()
+                        && let Some(output) = FileDialog::new()
+                            .add_filter("MP4", &["mp4"])
+                            .save_file()
+                    {
+                        self.export_remotely(ctx, output);
+                    }
+                });
+
+                ui.separator();
+                ui.label("Audio streams");
+                ui.horizontal(|ui| {
+                    ui.label("1. Timeline mix");
+                    ui.add(egui::TextEdit::singleline(&mut self.audio_tracks.language).char_limit(3).desired_width(40.0).hint_text("lang"));
+                });
+                let mut alt_to_remove = None;
+                for (i, alt) in self.audio_tracks.alternates.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}. {}", i + 2, alt.title())).on_hover_text(alt.path.display().to_string());
+                        ui.add(egui::TextEdit::singleline(&mut alt.language).char_limit(3).desired_width(40.0).hint_text("lang"));
+                        if ui.small_button("🗑").clicked() {
+                            alt_to_remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = alt_to_remove {
+                    self.audio_tracks.alternates.remove(i);
+                }
+                if ui.button("Add Alternate Audio…").on_hover_text("e.g. a dub or commentary recorded against the finished programme").clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("Audio", &["wav", "mp3", "m4a", "aac", "flac", "ogg", "opus"])
+                        .pick_file()
+                {
+                    self.audio_tracks.alternates.push(AltAudio { path, language: String::new() });
+                }
+            });
+
+            ui.collapsing("Program Output", |ui| {
+                let before = (self.program_output.clone(), self.program_output_enabled);
+                let device_edit = ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("program_output_format")
+                        .selected_text(&self.program_output.format)
+                        .show_ui(ui, |ui| {
+                            for format in player::PROGRAM_OUTPUT_FORMATS {
+                                ui.selectable_value(&mut self.program_output.format, format.to_string(), *format);
+                            }
+                        });
+                    let device_edit = ui.add(egui::TextEdit::singleline(&mut self.program_output.device).hint_text("device / source name"));
+                    ui.checkbox(&mut self.program_output_enabled, "Send during playback");
+                    device_edit
+                }).inner;
+                ui.label("Requires an ffmpeg build with the selected output device.");
+
+                // a running output restarts on every change, so the device name is sent once typed
+                if (before != (self.program_output.clone(), self.program_output_enabled) && !device_edit.has_focus())
+                    || device_edit.lost_focus()
+                {
+                    let output = (self.program_output_enabled && !self.program_output.device.is_empty())
+                        .then(|| self.program_output.clone());
+                    self.video_player.send_command(PlayerCommand::SetProgramOutput(output));
+                }
+            });
+
+            ui.collapsing(format!("Markers ({})", self.markers.len()), |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Import Review…").clicked()
+                        && let Some(path) = FileDialog::new()
+                            .add_filter("Review CSV", &["csv"])
+                            .pick_file()
+                    {
+                        match review::import_csv(&path) {
+                            Ok(imported) => {
+                                let count = imported.len();
+                                self.markers.extend(imported);
+                                self.markers.sort_by_key(|m| m.time);
+                                self.set_status(&format!("Imported {} review comment(s) as markers.", count));
+                            }
+                            Err(err) => self.set_status(err),
+                        }
+                    }
+                    if ui.add_enabled(!self.markers.is_empty(), egui::Button::new("YouTube Chapters…")).clicked() {
+                        let (text, warnings) = review::youtube_chapters(&self.output_chapters());
+                        self.chapters_text = Some(text);
+                        self.set_status(&warnings.join(" "));
+                    }
+                    ui.checkbox(&mut self.write_chapters_on_export, "Write chapters next to export");
+                    if ui.add_enabled(!self.markers.is_empty(), egui::Button::new("Export Review…")).clicked()
+                        && let Some(path) = FileDialog::new()
+                            .add_filter("Review CSV", &["csv"])
+                            .save_file()
+                    {
+                        match review::export_csv(&path, &self.markers) {
+                            Ok(()) => self.set_status("Markers exported for review."),
+                            Err(err) => self.set_status(err),
+                        }
+                    }
+                });
+
+                let mut marker_to_remove = None;
+                for (idx, marker) in self.markers.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.link(format_precise_timecode(marker.time)).clicked() {
+                            self.playhead = marker.time.min(self.total_timeline_duration);
+                        }
+                        ui.text_edit_singleline(&mut marker.note);
+                        if ui.small_button("🗑").clicked() {
+                            marker_to_remove = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = marker_to_remove {
+                    self.markers.remove(idx);
+                }
+            });
+
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Status: {}", self.status_message));
+                    if self.is_exporting { ui.add(egui::Spinner::new()); }
+                    if let Some((_, progress)) = &self.media_move {
+                        ui.add(egui::ProgressBar::new(*progress).desired_width(120.0).show_percentage());
+                    }
+                    if let Some((_, progress, _)) = &self.card_copy {
+                        ui.add(egui::ProgressBar::new(*progress).desired_width(120.0).show_percentage());
+                    }
+                    if let Some((_, progress, _)) = &self.local_export {
+                        ui.add(egui::ProgressBar::new(*progress).desired_width(120.0).show_percentage());
+                    }
+                    if let Some((_, progress)) = &self.remote_render {
+                        ui.add(egui::ProgressBar::new(*progress).desired_width(120.0).show_percentage());
+                    }
+                    if self.auto_preview.is_running() {
+                        ui.add(egui::Spinner::new()).on_hover_text("Rendering the review preview");
+                    }
+                });
+            });
+        });
+    }
+}
+
+impl VideoEditorApp {
+    fn set_status(&mut self, status: &str) {
+        self.status_message = status.to_string();
+        crash::record(status, &self.clips);
+    }
+
+    /// Copies card clips into the project's media folder (next to the project file,
+    /// or a chosen folder for unsaved projects), then imports the copies.
+    fn import_from_card(&mut self, ctx: &egui::Context, clips: Vec<card::CardClip>) {
+        let media_dir = match self.project_path.as_ref().and_then(|p| p.parent()) {
+            Some(dir) => dir.join("media"),
+            None => match FileDialog::new().set_title("Copy clips to").pick_folder() {
+                Some(dir) => dir,
+                None => return,
+            },
+        };
+        self.card_copy = Some((transfer::import_card(ctx, clips, media_dir.clone()), 0.0, media_dir));
+    }
+
+    /// Imports what a card copy brought in. A failure is reported after the import so the
+    /// statuses of the imported clips do not hide it.
+    fn finish_card_copy(&mut self, media_dir: &Path, done: Vec<PathBuf>, failed: usize, error: Option<&'static str>) {
+        let total = done.len() + failed;
+        for path in done {
+            self.import_media(path);
+        }
+        match error {
+            Some(err) => self.set_status(&format!("Card import stopped ({}); {} of {} clip(s) were not copied.", err, failed, total)),
+            None => self.set_status(&format!("Copied {} clip(s) from the card to {}.", total, media_dir.display())),
+        }
+    }
+
+    /// Renders the project's outro template and appends it like an imported file. It goes in
+    /// the project's media folder (the cache folder while unsaved), numbered so earlier outros
+    /// already on the timeline are kept.
+    fn append_outro(&mut self, ctx: &egui::Context) {
+        let dir = match self.project_path.as_ref().and_then(|p| p.parent()) {
+            Some(dir) => dir.join("media"),
+            None => settings::cache_dir().join("outros"),
+        };
+        if std::fs::create_dir_all(&dir).is_err() {
+            return self.set_status("Cannot create the outro folder");
+        }
+        let output = (1..).map(|n| dir.join(format!("outro-{}.mp4", n))).find(|p| !p.exists()).unwrap();
+
+        let last_clip = self.clips.iter().max_by_key(|c| c.timeline_start + (c.trim_end - c.trim_start)).cloned();
+        self.outro_render = Some((outro::start(ctx, self.outro.clone(), last_clip, output.clone()), output));
+        self.set_status("Rendering the outro ...");
+    }
+
+    /// Writes the clip to a lossless intermediate in the project's `roundtrip` folder (the
+    /// cache folder while unsaved) and opens it in the compositor.
+    fn send_to_compositor(&mut self, ctx: &egui::Context, idx: usize) {
+        let dir = match self.project_path.as_ref().and_then(|p| p.parent()) {
+            Some(dir) => dir.join("roundtrip"),
+            None => settings::cache_dir().join("roundtrip"),
+        };
+        self.round_trips_sending.push(roundtrip::start(ctx, self.clips[idx].clone(), dir));
+        self.set_status("Writing the round-trip intermediate ...");
+    }
+
+    /// Opens a written intermediate in the compositor and starts watching for its render.
+    fn round_trip_sent(&mut self, sent: Result<roundtrip::RoundTrip, &'static str>) {
+        match sent {
+            Ok(round_trip) => {
+                roundtrip::open_in(&settings::load().compositor, &round_trip.intermediate);
+                self.set_status(&format!(
+                    "Render the composite to {}.({}) next to the intermediate to replace the clip.",
+                    round_trip.result_stem(), roundtrip::RESULT_EXTENSIONS.join("|")
+                ));
+                self.round_trips.push(round_trip);
+            }
+            Err(err) => self.set_status(err),
+        }
+    }
 
-            while let Ok(_) = self.video_player.playback_ended_receiver.try_recv() {
-                if self.is_playing {
-                    self.pending_clip_transition = true;
-                    ctx.request_repaint();
-                }
+    /// Swaps the rendered composite in for the clip it was made from, keeping its place,
+    /// length and effects.
+    fn finish_round_trip(&mut self, round_trip: roundtrip::RoundTrip, result: PathBuf) {
+        let Some(idx) = self.clips.iter().position(|c| {
+            c.generator.is_none() && c.path == round_trip.source && c.trim_start == round_trip.trim_start && c.trim_end == round_trip.trim_end
+        }) else {
+            return self.set_status("Composite rendered, but its clip has since been changed or removed");
+        };
+        let duration = match get_video_duration(&result) {
+            Ok(duration) => duration,
+            Err(err) => return self.set_status(err),
+        };
+        if self.is_playing {
+            self.is_playing = false;
+            self.video_player.send_command(PlayerCommand::StopPlayback);
+        }
+
+        let name = result.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        if !self.media_bin.iter().any(|m| m.path == result) {
+            self.media_bin.push(MediaItem { stamp: file_stamp(&result), path: result.clone(), name: name.clone(), duration });
+        }
+        let clip = &mut self.clips[idx];
+        let length = clip.trim_end - clip.trim_start;
+        clip.path = result;
+        clip.name = name;
+        clip.duration = duration;
+        clip.trim_start = 0;
+        clip.trim_end = length.min(duration);
+        self.current_active_clip_id = None;
+        self.set_status("Clip replaced with the composited render.");
+    }
+
+    /// Re-probes every bin source and carries new durations over to its clips. Trims are
+    /// left alone; clips whose out point is now past the end are outlined and reported.
+    fn refresh_metadata(&mut self) {
+        let mut changed = 0;
+        let mut failed = 0;
+        for item in &mut self.media_bin {
+            let Ok(duration) = get_video_duration(&item.path) else {
+                failed += 1;
+                continue;
+            };
+            item.stamp = file_stamp(&item.path);
+            if duration == item.duration {
+                continue;
+            }
+            changed += 1;
+            item.duration = duration;
+            for clip in self.clips.iter_mut().filter(|c| c.generator.is_none() && c.path == item.path) {
+                clip.duration = duration;
             }
+        }
+        self.current_active_clip_id = None;
 
-            // request new clip to load
-            const MIN_FRAME_REQUEST_INTERVAL_MS_SCRUBBING: u32 = 300;
+        let overrun = self.clips.iter().filter(|c| c.generator.is_none() && c.trim_end > c.duration).count();
+        let mut status = format!("Metadata refreshed: {} source(s) changed length.", changed);
+        if failed > 0 {
+            status.push_str(&format!(" {} could not be read.", failed));
+        }
+        if overrun > 0 {
+            status.push_str(&format!(" {} clip(s) now end past their source (outlined in red).", overrun));
+        }
+        self.set_status(&status);
+    }
 
-            let active_clip_idx = self.clips.iter().position(|c| {
-                let clip_timeline_end = c.timeline_start + (c.trim_end - c.trim_start);
-                self.playhead >= c.timeline_start && self.playhead < clip_timeline_end
+    /// Adds a media file to the bin and the end of the timeline.
+    fn import_media(&mut self, path: PathBuf) {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        
+        let duration = match get_video_duration(&path) {
+            Ok(dur) => dur,
+            Err(err) => {
+                self.set_status(err);
+                10000
+            },
+        };
+
+        let item = MediaItem { stamp: file_stamp(&path), path, name, duration };
+        if !self.media_bin.iter().any(|m| m.path == item.path) {
+            self.media_bin.push(item.clone());
+        }
+        let timeline_start = self.append_to_timeline(&item);
+        if let Ok(data) = embedded::probe(&item.path)
+            && !data.is_empty()
+        {
+            self.embedded_import = Some(EmbeddedImport {
+                path: item.path,
+                timeline_start,
+                duration: item.duration,
+                add_chapters: !data.chapters.is_empty(),
+                extract_subtitles: data.subtitles.iter().any(|s| s.is_text()),
+                data,
             });
+        }
+    }
 
-            if let Some(clip_idx) = active_clip_idx {
-                let mut should_request_new_frame = false;
+    /// Puts a bin item at the playhead. Insert splits the clip under the playhead and pushes
+    /// everything after it right; overwrite replaces whatever lies under the new clip.
+    fn place_at_playhead(&mut self, item: &MediaItem, overwrite: bool) {
+        if self.is_playing {
+            self.is_playing = false;
+            self.video_player.send_command(PlayerCommand::StopPlayback);
+        }
+        let start = self.playhead;
+        let end = start + item.duration;
+        self.split_clip_at(start);
+        if overwrite {
+            self.split_clip_at(end);
+            self.clips.retain(|c| c.timeline_start < start || c.timeline_start >= end);
+        } else {
+            for clip in self.clips.iter_mut().filter(|c| c.timeline_start >= start) {
+                clip.timeline_start += item.duration;
+            }
+        }
+        self.clips.push(VideoClip {
+            path: item.path.clone(),
+            name: item.name.clone(),
+            duration: item.duration,
+            timeline_start: start,
+            trim_start: 0,
+            trim_end: item.duration,
+            ..Default::default()
+        });
 
-                let active_clip = &self.clips[clip_idx];
-                let clip_playhead_offset_ms = self.playhead - active_clip.timeline_start;
+        // export and output-time mapping walk the clips in order
+        self.clips.sort_by_key(|c| c.timeline_start);
 
-                if self.current_active_clip_id != Some(clip_idx) {
-                    // load new clip
-                    self.current_active_clip_id = Some(clip_idx);
-                    let active_clip = &self.clips[clip_idx];
-                    self.video_player.send_command(PlayerCommand::LoadClip {
-                        path: active_clip.path.clone(),
-                        trim_start_ms: active_clip.trim_start,
-                        trim_end_ms: active_clip.trim_end,
-                    });
-                    should_request_new_frame = true;
-                    self.last_requested_playhead_ms = u32::MAX;
+        self.total_timeline_duration = self.total_timeline_duration.max(end);
+        self.playhead = end;
+        self.selected_clip = self.clips.iter().position(|c| c.timeline_start == start);
+        self.trim_point = None;
+        self.current_active_clip_id = None;
+        self.set_status(if overwrite { "Clip overwritten at playhead." } else { "Clip inserted at playhead." });
+    }
 
-                    if self.is_playing {
-                        self.video_player.send_command(PlayerCommand::StartPlayback {
-                            timestamp_ms: clip_playhead_offset_ms,
-                        });
-                    }
-                }
+    /// Nearest place to ripple-insert a dragged clip: before another clip (by index), or None for the end.
+    fn ripple_target(&self, time: u32, moving: usize) -> Option<usize> {
+        let content_end = self.clips.iter().enumerate()
+            .filter(|(i, _)| *i != moving)
+            .map(|(_, c)| c.timeline_start + (c.trim_end - c.trim_start))
+            .fold(0, u32::max);
+        self.clips.iter().enumerate()
+            .filter(|(i, _)| *i != moving)
+            .map(|(i, c)| (Some(i), c.timeline_start))
+            .chain(std::iter::once((None, content_end)))
+            .min_by_key(|(_, boundary)| boundary.abs_diff(time))
+            .and_then(|(before, _)| before)
+    }
 
-                if !self.is_playing { // scrubbing
-                    let time_since_last_request = self.last_playhead_update_time.elapsed().as_millis() as u32;
+    /// Ripple move: lifts the clip out, closing the gap it leaves, then inserts it before
+    /// clip `before` (or at the end), pushing everything from there right.
+    fn ripple_move(&mut self, idx: usize, before: Option<usize>) {
+        if before == Some(idx) || before == Some(idx + 1) && self.clips.get(idx + 1).is_some_and(|next| {
+            next.timeline_start == self.clips[idx].timeline_start + (self.clips[idx].trim_end - self.clips[idx].trim_start)
+        }) {
+            return; // dropped where it already is
+        }
+        if self.is_playing {
+            self.is_playing = false;
+            self.video_player.send_command(PlayerCommand::StopPlayback);
+        }
 
-                    if should_request_new_frame ||
-                        (clip_playhead_offset_ms != self.last_requested_playhead_ms &&
-                        time_since_last_request >= MIN_FRAME_REQUEST_INTERVAL_MS_SCRUBBING) {
-                        
-                        self.video_player.send_command(PlayerCommand::Seek {
-                            timestamp_ms: clip_playhead_offset_ms,
-                        });
-                        self.last_requested_playhead_ms = clip_playhead_offset_ms;
-                        self.last_playhead_update_time = Instant::now();
-                    }
-                }
-            } else {
-                self.current_preview_texture = Some(ctx.load_texture(
-                    "video_preview_frame",
-                    egui::ColorImage::filled([PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize], egui::Color32::BLACK),
-                    egui::TextureOptions::LINEAR,
-                ));
-            }
+        let before_start = before.map(|j| self.clips[j].timeline_start);
+        let mut clip = self.clips.remove(idx);
+        let duration = clip.trim_end - clip.trim_start;
+        let lifted_end = clip.timeline_start + duration;
+        for other in self.clips.iter_mut().filter(|c| c.timeline_start >= lifted_end) {
+            other.timeline_start -= duration;
+        }
 
-            if self.is_playing {
-                ctx.request_repaint();
-            }
+        let insert_at = match before_start {
+            Some(start) if start >= lifted_end => start - duration,
+            Some(start) => start,
+            None => self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max),
+        };
+        for other in self.clips.iter_mut().filter(|c| c.timeline_start >= insert_at) {
+            other.timeline_start += duration;
+        }
+        clip.timeline_start = insert_at;
+        let pos = self.clips.partition_point(|c| c.timeline_start < insert_at);
+        self.clips.insert(pos, clip);
+
+        self.selected_clip = Some(pos);
+        self.trim_point = None;
+        self.current_active_clip_id = None;
+        self.set_status("Clip ripple-moved.");
+    }
 
-            ui.add_space(30.0);
+    /// Cuts the clip spanning `time` into two, so edits can start exactly there.
+    fn split_clip_at(&mut self, time: u32) {
+        let Some(idx) = self.clips.iter().position(|c| c.timeline_start < time && time < c.timeline_start + (c.trim_end - c.trim_start)) else {
+            return;
+        };
+        let mut right = self.clips[idx].clone();
+        let cut = right.trim_start + (time - right.timeline_start);
+        right.timeline_start = time;
+        right.trim_start = cut;
+        self.clips[idx].trim_end = cut;
+        self.clips.insert(idx + 1, right);
+    }
 
-            // timeline
-            ui.label("Timeline");
-            let timeline_height = 60.0;
-            let (timeline_rect, _resp) = ui.allocate_at_least(egui::vec2(ui.available_width(), timeline_height), egui::Sense::hover());
-            ui.painter().rect_filled(timeline_rect, 4.0, egui::Color32::from_gray(40));
+    /// Adds the whole of a bin item after the last clip on the timeline, returning where it starts.
+    fn append_to_timeline(&mut self, item: &MediaItem) -> u32 {
+        let offset = self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
+
+        self.clips.push(VideoClip {
+            path: item.path.clone(),
+            name: item.name.clone(),
+            duration: item.duration,
+            timeline_start: offset,
+            trim_start: 0,
+            trim_end: item.duration,
+            ..Default::default()
+        });
+        self.set_status("Clip added to timeline.");
+        offset
+    }
 
-            let time_to_x = |t: u32| timeline_rect.left() + (t as f32 / self.total_timeline_duration as f32) * timeline_rect.width();
-            let x_to_time = |x: f32| (((x - timeline_rect.left()) / timeline_rect.width()) * self.total_timeline_duration as f32).round() as u32;
+    fn import_embedded(&mut self, ctx: &egui::Context, pending: EmbeddedImport) {
+        let mut added = 0;
+        if pending.add_chapters {
+            for chapter in pending.data.chapters.iter().filter(|c| c.start_ms < pending.duration) {
+                self.markers.push(Marker { time: pending.timeline_start + chapter.start_ms, note: chapter.title.clone() });
+                added += 1;
+            }
+            self.markers.sort_by_key(|m| m.time);
+        }
 
-            let mut clip_to_update = None;
+        if pending.extract_subtitles {
+            self.subtitle_extraction = Some((embedded::start_extract(ctx, pending.path, pending.data.subtitles), added));
+            self.set_status("Extracting subtitles ...");
+        } else {
+            self.finish_embedded_import(added, embedded::Extracted::default());
+        }
+    }
 
-            for (idx, clip) in self.clips.iter().enumerate() {
-                let is_selected = self.selected_clip == Some(idx);
-                let clip_duration = clip.trim_end - clip.trim_start;
+    /// Reports an embedded import once its subtitles are written, failures last.
+    fn finish_embedded_import(&mut self, chapters: usize, extracted: embedded::Extracted) {
+        let mut status = format!("Added {} chapter marker(s), extracted {} subtitle file(s).", chapters, extracted.files);
+        if let Some(err) = extracted.error {
+            status.push_str(&format!(" {} stream(s) failed: {}.", extracted.failed, err));
+        }
+        self.set_status(&status);
+    }
 
-                let start_x = time_to_x(clip.timeline_start);
-                let end_x = time_to_x(clip.timeline_start + clip_duration);
-                
-                let clip_rect = egui::Rect::from_x_y_ranges(start_x..=end_x, timeline_rect.top()..=timeline_rect.bottom());
-                ui.painter().rect_filled(clip_rect, 2.0, if is_selected { egui::Color32::from_rgb(60, 60, 200) } else { egui::Color32::from_rgb(60, 120, 180) });
-                ui.painter().rect_stroke(clip_rect, 2.0, egui::Stroke::new(1.0, egui::Color32::WHITE), egui::StrokeKind::Inside);
+    /// Drops bin items no clip references, optionally moving their files into a chosen folder.
+    fn remove_unused_media(&mut self, ctx: &egui::Context, unused: &[MediaItem], move_files: bool) {
+        if !move_files {
+            self.media_bin.retain(|m| !unused.iter().any(|u| u.path == m.path));
+            return self.set_status(&format!("Removed {} unused item(s) from the media bin.", unused.len()));
+        }
+        let Some(destination) = FileDialog::new().set_title("Move unused media to").pick_folder() else {
+            return;
+        };
+        let paths = unused.iter().map(|item| item.path.clone()).collect();
+        self.media_move = Some((transfer::move_media(ctx, paths, destination), 0.0));
+    }
 
-                let handle_w = 10.0;
+    /// Drops moved files from the bin; files that could not be moved stay in it so nothing
+    /// is lost track of.
+    fn finish_media_move(&mut self, moved: Vec<PathBuf>, failed: usize, error: Option<&'static str>) {
+        self.media_bin.retain(|m| !moved.contains(&m.path));
+        match error {
+            Some(err) => self.set_status(&format!("Moved {} unused file(s), but {} could not be moved ({}).", moved.len(), failed, err)),
+            None => self.set_status(&format!("Moved {} unused file(s) out of the media bin.", moved.len())),
+        }
+    }
 
-                let middle_drag_rect = egui::Rect::from_x_y_ranges(
-                    (start_x + handle_w)..=(end_x - handle_w),
-                    timeline_rect.top()..=timeline_rect.bottom(),
-                );
-                let l_handle = egui::Rect::from_x_y_ranges(start_x..=(start_x + handle_w), timeline_rect.top()..=timeline_rect.bottom());
-                let r_handle = egui::Rect::from_x_y_ranges((end_x - handle_w)..=end_x, timeline_rect.top()..=timeline_rect.bottom());
+    /// Human-readable cut list: one line per clip with source and record in/out.
+    fn timeline_summary(&self) -> String {
+        let mut clips: Vec<&VideoClip> = self.clips.iter().collect();
+        clips.sort_by_key(|c| c.timeline_start);
 
-                let l_res = ui.interact(l_handle, egui::Id::new((idx, "l")), egui::Sense::drag());
-                let r_res = ui.interact(r_handle, egui::Id::new((idx, "r")), egui::Sense::drag());
+        let name_width = clips.iter().map(|c| c.name.chars().count()).max().unwrap_or(0).max(4);
+        let mut out = format!(
+            "{:<3}  {:<name_width$}  {:<12}  {:<12}  {:<12}  {:<12}\n",
+            "#", "Clip", "Source In", "Source Out", "Record In", "Record Out"
+        );
+        for (i, clip) in clips.iter().enumerate() {
+            let record_out = clip.timeline_start + (clip.trim_end - clip.trim_start);
+            out.push_str(&format!(
+                "{:<3}  {:<name_width$}  {}  {}  {}  {}\n",
+                i + 1,
+                clip.name,
+                format_precise_timecode(clip.trim_start),
+                format_precise_timecode(clip.trim_end),
+                format_precise_timecode(clip.timeline_start),
+                format_precise_timecode(record_out),
+            ));
+        }
+        out
+    }
 
-                let middle_res = ui.interact(middle_drag_rect, egui::Id::new((idx, "middle")), egui::Sense::drag());
+    /// Empty spans between clips (trailing space after the last clip is not a gap).
+    fn timeline_gaps(&self) -> Vec<(u32, u32)> {
+        let mut spans: Vec<(u32, u32)> = self.clips.iter()
+            .map(|c| (c.timeline_start, c.timeline_start + (c.trim_end - c.trim_start)))
+            .collect();
+        spans.sort();
+
+        let mut gaps = Vec::new();
+        let mut covered_until = 0;
+        for (start, end) in spans {
+            if start > covered_until {
+                gaps.push((covered_until, start));
+            }
+            covered_until = covered_until.max(end);
+        }
+        gaps
+    }
 
-                if l_res.hovered() || r_res.hovered() || l_res.dragged() || r_res.dragged() {
-                    ctx.set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
-                } else if middle_res.hovered() || middle_res.dragged() {
-                    ctx.set_cursor_icon(if middle_res.dragged() {
-                        egui::CursorIcon::Grabbing
-                    } else {
-                        egui::CursorIcon::Grab
-                    });
-                }
+    /// Moves the trim point by whole frames. When another clip butts against it the cut is rolled,
+    /// trimming both sides so nothing else on the timeline moves.
+    fn nudge_trim_point(&mut self, frames: i32) {
+        let Some((idx, side)) = self.trim_point else { return };
+        let Some((outgoing, incoming)) = self.trim_point_clips() else { return };
 
-                if l_res.dragged() {
-                    let timeline_end = clip.timeline_start + clip.trim_end - clip.trim_start;
-                    let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
-                    let new_timeline_start = x_to_time(pointer_x)
-                        .clamp(0, self.total_timeline_duration - MIN_CLIP_DURATION)
-                        .clamp(clip.timeline_start - clip.trim_start, timeline_end - MIN_CLIP_DURATION);
+        let mut delta = (frames as f32 * 1000.0 / TIMELINE_FPS).round() as i64;
+        if let Some(out) = outgoing.map(|i| &self.clips[i]) {
+            let lower = (out.trim_start + MIN_CLIP_DURATION) as i64 - out.trim_end as i64;
+            delta = delta.clamp(lower, (out.duration as i64 - out.trim_end as i64).max(lower));
+        }
+        if let Some(inc) = incoming.map(|i| &self.clips[i]) {
+            let lower = -(inc.trim_start.min(inc.timeline_start) as i64);
+            delta = delta.clamp(lower, (inc.trim_end as i64 - (inc.trim_start + MIN_CLIP_DURATION) as i64).max(lower));
+        }
+        if delta == 0 {
+            return;
+        }
 
-                    let new_trim_start = clip.trim_end - (timeline_end - new_timeline_start);
-                    
-                    clip_to_update = Some((idx, new_timeline_start, new_trim_start, clip.trim_end));
-                }
-                if r_res.dragged() {
-                    let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
-                    let new_timeline_end = x_to_time(pointer_x)
-                        .clamp(clip.timeline_start + MIN_CLIP_DURATION, self.total_timeline_duration);
-                    let new_trim_end = (clip.trim_start + (new_timeline_end - clip.timeline_start))
-                        .clamp(clip.trim_start + MIN_CLIP_DURATION, clip.duration);
-                    clip_to_update = Some((idx, clip.timeline_start, clip.trim_start, new_trim_end));
-                }
-                
-                if middle_res.drag_started() {
-                    println!("dragstart");
-                    self.clip_drag_init = clip.timeline_start;
-                    self.selected_clip = Some(idx);
-                }
+        if self.is_playing {
+            self.is_playing = false;
+            self.video_player.send_command(PlayerCommand::StopPlayback);
+        }
+        if let Some(i) = outgoing {
+            self.clips[i].trim_end = (self.clips[i].trim_end as i64 + delta) as u32;
+        }
+        if let Some(i) = incoming {
+            self.clips[i].trim_start = (self.clips[i].trim_start as i64 + delta) as u32;
+            self.clips[i].timeline_start = (self.clips[i].timeline_start as i64 + delta) as u32;
+        }
 
-                if middle_res.dragged() {
-                    let pointer_pos = ctx.input(|i| i.pointer.press_origin()).unwrap_or_default();
-                    let current_pos = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default());
-                    // println!("{} {}", pointer_pos, current_pos);
+        let content_end = self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
+        self.total_timeline_duration = self.total_timeline_duration.max(content_end);
+
+        // park the playhead on the trimmed frame: first of an in point, last of an out point
+        let clip = &self.clips[idx];
+        self.playhead = match side {
+            TrimSide::In => clip.timeline_start,
+            TrimSide::Out => (clip.timeline_start + (clip.trim_end - clip.trim_start)).saturating_sub((1000.0 / TIMELINE_FPS).round() as u32),
+        };
+        self.current_active_clip_id = None;
+        self.set_status(&format!("Trimmed {:+} frame(s).", (delta as f32 * TIMELINE_FPS / 1000.0).round() as i32));
+    }
 
-                    let prev = self.clips.iter()
-                        .map(|c| { c.timeline_start + c.trim_end - c.trim_start })
-                        .filter(|timeline_end| { *timeline_end <= clip.timeline_start })
-                        .max()
-                        .unwrap_or(0);
+    /// Puts a clip back to how it was imported: the whole source and no effects.
+    /// Clips after it ripple so a longer clip does not overlap them.
+    fn revert_clip(&mut self, idx: usize) {
+        let clip = &self.clips[idx];
+        let old_end = clip.timeline_start + (clip.trim_end - clip.trim_start);
+        let reverted = VideoClip {
+            path: clip.path.clone(),
+            name: clip.name.clone(),
+            duration: clip.duration,
+            timeline_start: clip.timeline_start,
+            trim_start: 0,
+            // generators have no source length, keep their length on the timeline
+            trim_end: if clip.generator.is_some() { clip.trim_end - clip.trim_start } else { clip.duration },
+            generator: clip.generator,
+            ..Default::default()
+        };
+        // usually the clip grows, but a source that got shorter since it was trimmed
+        // (see Refresh Metadata) leaves it shorter than it was, pulling later clips left
+        let shift = (reverted.timeline_start + reverted.trim_end) as i64 - old_end as i64;
+        self.clips[idx] = reverted;
+
+        for (i, other) in self.clips.iter_mut().enumerate() {
+            if i != idx && other.timeline_start >= old_end {
+                // later clips start at or after old_end, so a left shift cannot pass zero
+                other.timeline_start = other.timeline_start.saturating_add_signed(shift as i32);
+            }
+        }
+        let content_end = self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
+        self.total_timeline_duration = self.total_timeline_duration.max(content_end);
+        self.current_active_clip_id = None;
+        self.set_status("Clip reverted to its original state.");
+    }
 
-                    let timeline_end = clip.timeline_start + clip.trim_end - clip.trim_start;
+    /// (outgoing, incoming) clip indices at the trim point; one side is None if nothing butts against it.
+    fn trim_point_clips(&self) -> Option<(Option<usize>, Option<usize>)> {
+        let (idx, side) = self.trim_point?;
+        let clip = self.clips.get(idx)?;
+        let clip_end = clip.timeline_start + (clip.trim_end - clip.trim_start);
+        Some(match side {
+            TrimSide::Out => (Some(idx), self.clips.iter().position(|c| c.timeline_start == clip_end)),
+            TrimSide::In => (self.clips.iter().position(|c| c.timeline_start + (c.trim_end - c.trim_start) == clip.timeline_start), Some(idx)),
+        })
+    }
 
-                    let next = self.clips.iter()
-                        .map(|c| { c.timeline_start })
-                        .filter(|timeline_start| { *timeline_start >= timeline_end })
-                        .min()
-                        .unwrap_or(self.total_timeline_duration)
-                         - clip_duration;
+    /// Decodes the frames either side of the trim point for the two-up preview.
+    fn refresh_trim_frames(&mut self, ctx: &egui::Context) {
+        let Some((outgoing, incoming)) = self.trim_point_clips() else {
+            self.trim_frames = None;
+            return;
+        };
+        let size = [PREVIEW_WIDTH / 2, PREVIEW_HEIGHT / 2];
+        let frame_ms = (1000.0 / TIMELINE_FPS).round() as u32;
+        let grab = |idx: Option<usize>, source_ms: &dyn Fn(&VideoClip) -> u32, name: &str| {
+            let clip = &self.clips[idx?];
+            let image = match clip.generator {
+                Some([r, g, b]) => egui::ColorImage::filled([size[0] as usize, size[1] as usize], egui::Color32::from_rgb(r, g, b)),
+                None => player::grab_frame(&clip.path, source_ms(clip), &clip.preview_filters(), size)?,
+            };
+            Some(ctx.load_texture(name, image, egui::TextureOptions::LINEAR))
+        };
+        self.trim_frames = Some([
+            grab(outgoing, &|c| c.trim_end.saturating_sub(frame_ms), "trim_out_frame"),
+            grab(incoming, &|c| c.trim_start, "trim_in_frame"),
+        ]);
+    }
 
-                    // println!("{} {}   {}", prev, next, x_to_time(time_to_x(self.clip_drag_init) + current_pos.x - pointer_pos.x));
-                    let new_timeline_start = x_to_time(time_to_x(self.clip_drag_init) + current_pos.x - pointer_pos.x)
-                        .clamp(prev, next.max(0));
-                    
-                    clip_to_update = Some((idx, new_timeline_start, clip.trim_start, clip.trim_end));
-                }
+    /// Changes a gap's length by shifting every clip after it; a length of zero is a ripple delete.
+    fn resize_gap(&mut self, gap_start: u32, gap_end: u32, new_len: u32) {
+        let new_end = gap_start + new_len;
+        for clip in self.clips.iter_mut().filter(|c| c.timeline_start >= gap_end) {
+            clip.timeline_start = clip.timeline_start - gap_end + new_end;
+        }
+        let content_end = self.clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
+        self.total_timeline_duration = self.total_timeline_duration.max(content_end);
+        self.selected_gap = (new_len > 0).then_some((gap_start, new_end));
+        self.current_active_clip_id = None;
+    }
 
-                if middle_res.drag_stopped() {
-                    self.clip_drag_init = 0;
-                }
+    /// Scales the visible span by `factor`, keeping `anchor_ms` at the same screen position.
+    fn zoom_timeline(&mut self, factor: f32, anchor_ms: u32) {
+        const MIN_VIEW_LEN: u32 = 200; // close enough to pick out mouth clicks in the waveform
+        let old_len = self.timeline_view_len as f32;
+        let new_len = (old_len * factor).clamp(MIN_VIEW_LEN as f32, self.total_timeline_duration as f32);
+        let anchor_frac = (anchor_ms as f32 - self.timeline_view_start as f32) / old_len;
+        self.timeline_view_len = new_len.round() as u32;
+        self.timeline_view_start = (anchor_ms as f32 - anchor_frac * new_len).max(0.0).round() as u32;
+        self.scroll_timeline(0.0);
+    }
 
-                ui.painter().rect_filled(l_handle, 2.0, egui::Color32::LIGHT_GREEN);
-                ui.painter().rect_filled(r_handle, 2.0, egui::Color32::LIGHT_GREEN);
+    fn scroll_timeline(&mut self, delta_ms: f32) {
+        let max_start = self.total_timeline_duration.saturating_sub(self.timeline_view_len);
+        self.timeline_view_start = (self.timeline_view_start as f32 + delta_ms).clamp(0.0, max_start as f32).round() as u32;
+    }
 
-                ui.painter().text(clip_rect.left_top() + egui::vec2(5.0, 15.0), egui::Align2::LEFT_TOP, &clip.name, egui::FontId::proportional(12.0), egui::Color32::WHITE);
+    fn save_project(&mut self, ctx: &egui::Context, path: PathBuf) {
+        // prefer the chosen export poster frame, else the start of the first clip
+        let poster_source = self.poster_frame
+            .and_then(|t| self.clips.iter()
+                .find(|c| t >= c.timeline_start && t < c.timeline_start + (c.trim_end - c.trim_start))
+                .map(|c| (c, c.trim_start + t - c.timeline_start)))
+            .or_else(|| self.clips.iter().min_by_key(|c| c.timeline_start).map(|c| (c, c.trim_start)));
+        let poster = poster_source.and_then(|(clip, source_ms)| project::capture_poster(&clip.path, source_ms));
+
+        let contents = project::Project {
+            info: project::ProjectInfo { duration_ms: 0, clip_count: 0, poster_png: poster },
+            media: self.media_bin.clone(),
+            clips: self.clips.clone(),
+            markers: self.markers.clone(),
+            audio_tracks: self.audio_tracks.clone(),
+            outro: self.outro.clone(),
+            poster_frame: self.poster_frame,
+        };
+        match project::save(&path, &contents) {
+            Ok(()) => {
+                self.set_status("Project saved.");
+                self.recent_projects = load_recent_projects(ctx, project::push_recent(&path));
+                self.project_path = Some(path);
+                self.project_read_only = false;
             }
+            Err(err) => self.set_status(err),
+        }
+    }
 
-            if let Some((idx, new_timeline_start, new_start, new_end)) = clip_to_update {
-                // stop playback when editing
+    fn open_project(&mut self, ctx: &egui::Context, path: PathBuf, read_only: bool) {
+        match project::load(&path) {
+            Ok(loaded) => {
                 if self.is_playing {
                     self.is_playing = false;
                     self.video_player.send_command(PlayerCommand::StopPlayback);
                 }
-                
-                self.clips[idx].timeline_start = new_timeline_start;
-                self.clips[idx].trim_start = new_start;
-                self.clips[idx].trim_end = new_end;
+                self.media_bin = loaded.media;
+                // projects saved before the bin existed only list clips
+                for clip in loaded.clips.iter().filter(|c| c.generator.is_none()) {
+                    if !self.media_bin.iter().any(|m| m.path == clip.path) {
+                        self.media_bin.push(MediaItem { path: clip.path.clone(), name: clip.name.clone(), duration: clip.duration, stamp: None });
+                    }
+                }
+                self.clips = loaded.clips;
+                self.markers = loaded.markers;
+                self.audio_tracks = loaded.audio_tracks;
+                self.outro = loaded.outro;
+                self.poster_frame = loaded.poster_frame;
+                self.playhead = 0;
+                self.selected_clip = None;
+                self.trim_point = None;
+                self.trim_frames = None;
+                self.current_active_clip_id = None;
+                self.set_status(if read_only { "Project opened read-only." } else { "Project opened." });
+                let changed = self.media_bin.iter().filter(|m| m.changed_on_disk()).count();
+                if changed > 0 {
+                    self.set_status(&format!("{} source file(s) changed since the project was saved; use Refresh Metadata in the Media Bin.", changed));
+                }
+                self.recent_projects = load_recent_projects(ctx, project::push_recent(&path));
+                self.project_path = Some(path);
+                self.project_read_only = read_only;
             }
+            Err(err) => self.set_status(err),
+        }
+    }
 
-            let ph_x = time_to_x(self.playhead);
+    /// Background jobs yield to anything realtime or heavy: playback, export, and battery saver.
+    /// Source the preview decodes for a clip: its proxy when proxies are on and one exists.
+    /// Cropped clips stay on the original since crop boxes are in source pixels.
+    fn preview_path(&mut self, idx: usize) -> PathBuf {
+        let path = self.clips[idx].path.clone();
+        if !self.use_proxies || self.clips[idx].crop.is_some() {
+            return path;
+        }
+        self.proxy_for(&path).unwrap_or(path)
+    }
 
-            
-            let ph_rect = egui::Rect::from_x_y_ranges(ph_x-1.0..=ph_x+1.0, timeline_rect.top()-20.0..=timeline_rect.bottom());
-            ui.painter().rect_filled(ph_rect, 2.0, egui::Color32::RED);
+    fn proxy_for(&mut self, path: &PathBuf) -> Option<PathBuf> {
+        self.proxies.entry(path.clone())
+            .or_insert_with(|| media_jobs::proxy_file(path).filter(|file| file.exists()))
+            .clone()
+    }
 
-            let ph_jump_rect = egui::Rect::from_min_max(egui::pos2(timeline_rect.min.x, timeline_rect.min.y - 20.0), timeline_rect.max);
-            
-            let ph_jump_res = ui.interact(ph_jump_rect, egui::Id::new("ph_jump"), egui::Sense::drag());
+    fn update_background_jobs(&self) {
+        self.media_jobs.set_paused(self.low_power || self.is_playing || self.is_exporting);
+    }
 
-            if ph_jump_res.dragged() {
-                let pointer_x = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default()).x;
-                self.playhead = x_to_time(pointer_x);
-            }
+    fn export_sequence(&mut self, ctx: &egui::Context, output: PathBuf) {
+        let stems = if self.export_audio && self.export_stems {
+            Stem::ALL.into_iter()
+                .filter(|&stem| self.clips.iter().any(|c| c.generator.is_none() && c.stem == stem))
+                .map(|stem| (stem.label(), self.stem_args(stem, &output.with_extension(format!("{}.wav", stem.key())))))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let job = export::ExportJob {
+            args: self.export_args(&output),
+            duration_ms: self.clips.iter().map(|c| c.trim_end - c.trim_start).sum(),
+            // stems are rendered as `<output>.<stem>.wav`, aligned with the main export
+            stems,
+            manifest: self.write_manifest_on_export.then(|| (self.export_sources(), self.clips.clone())),
+            chapters: (self.write_chapters_on_export && !self.markers.is_empty())
+                .then(|| review::youtube_chapters(&self.output_chapters()).0),
+            poster_ms: self.poster_frame.filter(|_| self.export_video).and_then(|t| self.timeline_to_output_time(t)),
+            output,
+            verify: None,
+            done: "exported successfully!".to_string(),
+            failed: "export failed!".to_string(),
+        };
+        self.start_export(ctx, "Exporting video ...", job);
+    }
 
+    /// Renders every output of a pipeline file in one ffmpeg run, named after `base`.
+    fn export_pipeline(&mut self, ctx: &egui::Context, pipeline_file: &Path, base: PathBuf) {
+        let outputs = match pipeline::load(pipeline_file) {
+            Ok(outputs) => outputs,
+            Err(err) => return self.set_status(err),
+        };
+        let video = outputs.iter().any(|o| o.video);
+        let audio = outputs.iter().any(|o| o.audio);
+        let args = match pipeline::build_args(&self.render_args(&base, video, audio, None), &outputs, &base) {
+            Ok(args) => args,
+            Err(err) => return self.set_status(err),
+        };
+        self.start_export(ctx, "Running pipeline ...", export::ExportJob {
+            output: outputs[0].path(&base),
+            args,
+            duration_ms: self.clips.iter().map(|c| c.trim_end - c.trim_start).sum(),
+            done: format!("Pipeline wrote {} file(s).", outputs.len()),
+            failed: "pipeline export failed!".to_string(),
+            ..Default::default()
+        });
+    }
 
-            // ui.painter().line_segment(
-            //     [egui::pos2(ph_x, rect.top() - 30.0), egui::pos2(ph_x, rect.bottom())],
-            //     egui::Stroke::new(3.0, egui::Color32::RED),
-            // );
+    /// Hands a render to the export worker; background jobs pause until it finishes.
+    fn start_export(&mut self, ctx: &egui::Context, status: &str, job: export::ExportJob) {
+        self.is_exporting = true;
+        self.update_background_jobs();
+        self.set_status(status);
+        let output = job.output.clone();
+        self.local_export = Some((export::start(job, ctx.clone()), 0.0, output));
+    }
 
-            ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-                ui.horizontal(|ui| {
-                    ui.label(format!("Status: {}", self.status_message));
-                    if self.is_exporting { ui.add(egui::Spinner::new()); }
-                });
-            });
-        });
+    fn export_remotely(&mut self, ctx: &egui::Context, output: PathBuf) {
+        let mut settings = settings::load();
+        settings.remote = self.remote_target.clone();
+        let _ = settings::save(&settings);
+
+        let sources = self.export_sources();
+        let duration_ms = self.clips.iter().map(|c| c.trim_end - c.trim_start).sum();
+
+        let args = self.export_args(&output);
+        self.remote_render = Some((remote::start(self.remote_target.clone(), args, sources, output, duration_ms, ctx.clone()), 0.0));
+        self.set_status("Starting remote render ...");
     }
-}
 
-impl VideoEditorApp {
-    fn set_status(&mut self, status: &str) {
-        self.status_message = status.to_string();
+    /// Every file `export_args` reads: clip sources, then alternate-language audio when exported.
+    fn export_sources(&self) -> Vec<PathBuf> {
+        let mut sources: Vec<PathBuf> = Vec::new();
+        let clip_sources = self.clips.iter().filter(|c| c.generator.is_none()).map(|c| &c.path);
+        let audio_sources = self.audio_tracks.alternates.iter().filter(|_| self.export_audio).map(|a| &a.path);
+        for path in clip_sources.chain(audio_sources) {
+            if !sources.contains(path) {
+                sources.push(path.clone());
+            }
+        }
+        sources
     }
 
-    fn export_sequence(&mut self, output: PathBuf) {
-        self.is_exporting = true;
-        self.set_status("Exporting video ...");
+    /// ffmpeg arguments rendering the timeline to `output`, which is always the last argument.
+    fn export_args(&self, output: &Path) -> Vec<String> {
+        self.render_args(output, self.export_video, self.export_audio, None)
+    }
+
+    /// Audio-only render of one stem: the programme with every other stem's clips silenced.
+    fn stem_args(&self, stem: Stem, output: &Path) -> Vec<String> {
+        self.render_args(output, false, true, Some(stem))
+    }
 
+    fn render_args(&self, output: &Path, export_video: bool, export_audio: bool, stem: Option<Stem>) -> Vec<String> {
         let mut cmd = Command::new("ffmpeg");
         cmd.arg("-y");
 
+        // (video, audio) input index per clip; generators take two lavfi inputs
+        let mut clip_inputs = Vec::new();
+        let mut input_count = 0;
         for clip in &self.clips {
-            cmd.arg("-ss").arg(format!("{:.2}", clip.trim_start as f32 / 1000.0))
-               .arg("-t").arg(format!("{:.2}", (clip.trim_end - clip.trim_start) as f32 / 1000.0))
-               .arg("-i").arg(&clip.path);
+            let clip_secs = (clip.trim_end - clip.trim_start) as f32 / 1000.0;
+            match clip.generator {
+                Some([r, g, b]) => {
+                    cmd.arg("-f").arg("lavfi")
+                       .arg("-t").arg(format!("{:.2}", clip_secs))
+                       .arg("-i").arg(format!("color=c=0x{:02x}{:02x}{:02x}:s=1920x1080:r=30", r, g, b))
+                       .arg("-f").arg("lavfi")
+                       .arg("-t").arg(format!("{:.2}", clip_secs))
+                       .arg("-i").arg("anullsrc=r=48000:cl=stereo");
+                    clip_inputs.push((input_count, input_count + 1));
+                    input_count += 2;
+                }
+                None => {
+                    cmd.arg("-ss").arg(format!("{:.2}", clip.trim_start as f32 / 1000.0))
+                       .arg("-t").arg(format!("{:.2}", clip_secs))
+                       .arg("-i").arg(&clip.path);
+                    clip_inputs.push((input_count, input_count));
+                    input_count += 1;
+                }
+            }
+        }
+
+        // alternates are cut to the programme length, which is the clips back to back
+        let programme_secs = self.clips.iter().map(|c| c.trim_end - c.trim_start).sum::<u32>() as f32 / 1000.0;
+        let alt_first_input = input_count;
+        let alternates = if export_audio && stem.is_none() { self.audio_tracks.alternates.as_slice() } else { &[] };
+        for alt in alternates {
+            cmd.arg("-t").arg(format!("{:.2}", programme_secs))
+               .arg("-i").arg(&alt.path);
         }
 
         let mut filter_parts = Vec::new();
-        for i in 0..self.clips.len() {
-            filter_parts.push(format!("[{}:v]scale=w=1920:h=1080:force_original_aspect_ratio=decrease,pad=1920:1080:(ow-iw)/2:(oh-ih)/2,setsar=1,setdar=16/9[v{}];", i, i));
+        for (i, clip) in self.clips.iter().enumerate().filter(|_| export_video) {
+            filter_parts.push(format!("[{}:v]{}scale=w=1920:h=1080:force_original_aspect_ratio=decrease,pad=1920:1080:(ow-iw)/2:(oh-ih)/2,setsar=1,setdar=16/9[v{}];", clip_inputs[i].0, clip.video_filters(), i));
         }
         
+        for (i, (_, audio_input)) in clip_inputs.iter().enumerate().filter(|_| export_audio) {
+            let clip = &self.clips[i];
+            let fades = self.audio_tracks.cut_fade_filters(i, self.clips.len(), (clip.trim_end - clip.trim_start) as f32 / 1000.0);
+            let mute = if stem.is_some_and(|stem| stem != clip.stem) { "volume=0," } else { "" };
+            filter_parts.push(format!("[{}:a]{}{}{}{}[a{}];", audio_input, clip.audio_filters(), fades, mute, self.channel_layout.filter(), i));
+        }
+
         let mut concat_inputs = String::new();
-        for i in 0..self.clips.len() {
-            concat_inputs.push_str(&format!("[v{}][{}:a]", i, i));
+        for i in 0..clip_inputs.len() {
+            if export_video {
+                concat_inputs.push_str(&format!("[v{}]", i));
+            }
+            if export_audio {
+                concat_inputs.push_str(&format!("[a{}]", i));
+            }
         }
         
         let filter_complex = format!(
-            "{}{}concat=n={}:v=1:a=1[outv][outa]",
+            "{}{}concat=n={}:v={}:a={}{}{}",
             filter_parts.join(""),
             concat_inputs,
-            self.clips.len()
+            self.clips.len(),
+            export_video as u8,
+            export_audio as u8,
+            if export_video { "[outv]" } else { "" },
+            if export_audio { "[outa]" } else { "" },
         );
         
-        cmd.arg("-filter_complex")
-           .arg(filter_complex)
-           .arg("-map").arg("[outv]")
-           .arg("-map").arg("[outa]")
-           .arg(output);
+        cmd.arg("-filter_complex").arg(filter_complex);
+        if export_video {
+            cmd.arg("-map").arg("[outv]");
+        }
+        if export_audio {
+            cmd.arg("-map").arg("[outa]");
+        }
+        if stem.is_some() {
+            cmd.arg("-c:a").arg("pcm_s24le");
+        }
+        for i in 0..alternates.len() {
+            cmd.arg("-map").arg(format!("{}:a:0", alt_first_input + i));
+        }
+        if !alternates.is_empty() {
+            cmd.arg("-c:a").arg("aac")
+               .arg("-metadata:s:a:0").arg("title=Main")
+               .arg("-disposition:a:0").arg("default");
+        }
+        let languages = std::iter::once(&self.audio_tracks.language).chain(alternates.iter().map(|a| &a.language));
+        for (i, language) in languages.enumerate().filter(|(_, l)| export_audio && stem.is_none() && !l.trim().is_empty()) {
+            cmd.arg(format!("-metadata:s:a:{}", i)).arg(format!("language={}", language.trim()));
+        }
+        for (i, alt) in alternates.iter().enumerate() {
+            cmd.arg(format!("-metadata:s:a:{}", i + 1)).arg(format!("title={}", alt.title()))
+               .arg(format!("-disposition:a:{}", i + 1)).arg("0");
+        }
+        cmd.arg(output);
 
-        let status = cmd.status();
+        cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect()
+    }
+
+    fn rerender_from_manifest(&mut self, ctx: &egui::Context, manifest_path: &Path) {
+        let manifest = match manifest::load(manifest_path) {
+            Ok(manifest) => manifest,
+            Err(err) => return self.set_status(err),
+        };
+
+        let original_name = Path::new(&manifest.output).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let Some(output) = FileDialog::new()
+            .add_filter("MP4", &["mp4"])
+            .set_file_name(&original_name)
+            .save_file()
+        else {
+            return;
+        };
+
+        // sources are hashed on the worker before the render starts
+        self.start_export(ctx, "Verifying manifest sources ...", export::ExportJob {
+            output,
+            duration_ms: manifest.duration_ms,
+            verify: Some((manifest, manifest_path.to_path_buf())),
+            done: "re-rendered successfully!".to_string(),
+            failed: "re-render failed!".to_string(),
+            ..Default::default()
+        });
+    }
 
-        match status {
-            Ok(s) if s.success() => self.set_status("exported successfully!"),
-            _ => self.set_status("export failed!"),
+    /// Markers as (exported file ms, title); markers over gaps are dropped since gaps are not exported.
+    fn output_chapters(&self) -> Vec<(u32, String)> {
+        self.markers.iter()
+            .filter_map(|m| Some((self.timeline_to_output_time(m.time)?, m.note.clone())))
+            .collect()
+    }
+
+    /// Maps a timeline position to the matching position in the exported file,
+    /// which concatenates clips back to back without gaps.
+    fn timeline_to_output_time(&self, timeline_ms: u32) -> Option<u32> {
+        let mut output_ms = 0;
+        for clip in &self.clips {
+            let clip_duration = clip.trim_end - clip.trim_start;
+            if timeline_ms >= clip.timeline_start && timeline_ms < clip.timeline_start + clip_duration {
+                return Some(output_ms + timeline_ms - clip.timeline_start);
+            }
+            output_ms += clip_duration;
         }
-        self.is_exporting = false;
+        None
     }
 }
@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::hash;
+use crate::VideoClip;
+
+// Export manifests are JSON written next to a deliverable:
+// {
+//   "format": "videoedit-manifest", "version": 1,
+//   "output": "...", "created_unix": 0,
+//   "ffmpeg_args": ["-y", ...],
+//   "sources": [{ "path": "...", "size": 0, "sha256": "..." }],
+//   "clips": [{ "source": 0, "timeline_start": 0, "trim_start": 0, "trim_end": 0, "filters": "..." }]
+// }
+pub const MANIFEST_FORMAT: &str = "videoedit-manifest";
+
+/// `sources` lists every input file `ffmpeg_args` reads (clip sources and any other audio),
+/// each once; all of them are hashed so a re-render can check them.
+pub fn write(manifest_path: &Path, output: &Path, ffmpeg_args: &[String], sources: &[PathBuf], clips: &[VideoClip]) -> Result<(), &'static str> {
+
+    let created = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut out = String::from("{\n");
+    out.push_str(&format!("  \"format\": {},\n  \"version\": 1,\n", quote(MANIFEST_FORMAT)));
+    out.push_str(&format!("  \"output\": {},\n", quote(&output.to_string_lossy())));
+    out.push_str(&format!("  \"created_unix\": {},\n", created));
+
+    let args: Vec<String> = ffmpeg_args.iter().map(|a| quote(a)).collect();
+    out.push_str(&format!("  \"ffmpeg_args\": [{}],\n", args.join(", ")));
+
+    out.push_str("  \"sources\": [\n");
+    for (i, source) in sources.iter().enumerate() {
+        let size = fs::metadata(source).map(|m| m.len()).map_err(|_| "Error reading source for manifest")?;
+        let sha256 = hash::sha256_file(source).map_err(|_| "Error hashing source for manifest")?;
+        out.push_str(&format!(
+            "    {{ \"path\": {}, \"size\": {}, \"sha256\": {} }}{}\n",
+            quote(&source.to_string_lossy()),
+            size,
+            quote(&sha256),
+            if i + 1 < sources.len() { "," } else { "" }
+        ));
+    }
+    out.push_str("  ],\n");
+
+    out.push_str("  \"clips\": [\n");
+    for (i, clip) in clips.iter().enumerate() {
+        let source = match clip.generator {
+            Some(_) => "null".to_string(),
+            None => sources.iter().position(|s| *s == clip.path).unwrap_or(0).to_string(),
+        };
+        out.push_str(&format!(
+            "    {{ \"source\": {}, \"timeline_start\": {}, \"trim_start\": {}, \"trim_end\": {}, \"filters\": {} }}{}\n",
+            source,
+            clip.timeline_start,
+            clip.trim_start,
+            clip.trim_end,
+            quote(&clip.video_filters()),
+            if i + 1 < clips.len() { "," } else { "" }
+        ));
+    }
+    out.push_str("  ]\n}\n");
+
+    fs::write(manifest_path, out).map_err(|_| "Error writing manifest")
+}
+
+fn quote(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub struct ManifestSource {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+pub struct Manifest {
+    pub output: String,
+    pub ffmpeg_args: Vec<String>,
+    pub sources: Vec<ManifestSource>,
+    pub duration_ms: u32, // length of the render, from the clip records
+}
+
+pub fn load(path: &Path) -> Result<Manifest, &'static str> {
+    let text = fs::read_to_string(path).map_err(|_| "Error reading manifest")?;
+    let root = Json::parse(&text).ok_or("Manifest is not valid JSON")?;
+    if root.get("format").and_then(Json::as_str) != Some(MANIFEST_FORMAT) {
+        return Err("Not an export manifest");
+    }
+
+    let output = root.get("output").and_then(Json::as_str).ok_or("Manifest has no output")?.to_string();
+    let ffmpeg_args = root.get("ffmpeg_args")
+        .and_then(Json::as_array)
+        .ok_or("Manifest has no ffmpeg arguments")?
+        .iter()
+        .map(|a| a.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Malformed ffmpeg arguments in manifest")?;
+    let sources = root.get("sources")
+        .and_then(Json::as_array)
+        .ok_or("Manifest has no sources")?
+        .iter()
+        .map(|s| Some(ManifestSource {
+            path: s.get("path")?.as_str()?.to_string(),
+            size: s.get("size")?.as_f64()? as u64,
+            sha256: s.get("sha256")?.as_str()?.to_string(),
+        }))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Malformed source entry in manifest")?;
+
+    let duration_ms = root.get("clips")
+        .and_then(Json::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|c| Some(c.get("trim_end")?.as_f64()? - c.get("trim_start")?.as_f64()?))
+        .sum::<f64>() as u32;
+
+    Ok(Manifest { output, ffmpeg_args, sources, duration_ms })
+}
+
+/// Checks every source by size and hash. A source missing from its recorded path is also
+/// looked for by file name next to the manifest, and the arguments are rewritten to use it.
+/// Returns the arguments to run, or a description of the first source that does not match.
+pub fn verified_args(manifest: &Manifest, manifest_path: &Path) -> Result<Vec<String>, String> {
+    let mut args = manifest.ffmpeg_args.clone();
+    for source in &manifest.sources {
+        let recorded = Path::new(&source.path);
+        let nearby = manifest_path.parent().zip(recorded.file_name()).map(|(dir, name)| dir.join(name));
+        let candidates = std::iter::once(recorded.to_path_buf()).chain(nearby);
+
+        let mut found = None;
+        for candidate in candidates {
+            let size_matches = fs::metadata(&candidate).is_ok_and(|m| m.len() == source.size);
+            if size_matches && hash::sha256_file(&candidate).is_ok_and(|h| h == source.sha256) {
+                found = Some(candidate);
+                break;
+            }
+        }
+
+        let found = found.ok_or_else(|| format!("Source missing or changed: {}", source.path))?;
+        let found = found.to_string_lossy().into_owned();
+        for arg in args.iter_mut().filter(|a| **a == source.path) {
+            *arg = found.clone();
+        }
+    }
+    Ok(args)
+}
+
+/// Just enough JSON to read manifests back.
+enum Json {
+    Literal, // true/false/null, never read by manifests
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Option<Json> {
+        let mut chars = text.chars().peekable();
+        let value = Self::parse_value(&mut chars)?;
+        Self::skip_whitespace(&mut chars);
+        chars.next().is_none().then_some(value)
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Json> {
+        Self::skip_whitespace(chars);
+        match *chars.peek()? {
+            '{' => {
+                chars.next();
+                let mut fields = Vec::new();
+                loop {
+                    Self::skip_whitespace(chars);
+                    if chars.next_if_eq(&'}').is_some() {
+                        return Some(Json::Object(fields));
+                    }
+                    let Json::String(key) = Self::parse_value(chars)? else { return None };
+                    Self::skip_whitespace(chars);
+                    chars.next_if_eq(&':')?;
+                    fields.push((key, Self::parse_value(chars)?));
+                    Self::skip_whitespace(chars);
+                    chars.next_if_eq(&',');
+                }
+            }
+            '[' => {
+                chars.next();
+                let mut items = Vec::new();
+                loop {
+                    Self::skip_whitespace(chars);
+                    if chars.next_if_eq(&']').is_some() {
+                        return Some(Json::Array(items));
+                    }
+                    items.push(Self::parse_value(chars)?);
+                    Self::skip_whitespace(chars);
+                    chars.next_if_eq(&',');
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => return Some(Json::String(s)),
+                        '\\' => match chars.next()? {
+                            'n' => s.push('\n'),
+                            'r' => s.push('\r'),
+                            't' => s.push('\t'),
+                            'b' => s.push('\u{8}'),
+                            'f' => s.push('\u{c}'),
+                            'u' => {
+                                let code: String = (0..4).map(|_| chars.next()).collect::<Option<_>>()?;
+                                s.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                            }
+                            c => s.push(c),
+                        },
+                        c => s.push(c),
+                    }
+                }
+            }
+            't' | 'f' | 'n' => {
+                let word: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_alphabetic())).collect();
+                matches!(word.as_str(), "true" | "false" | "null").then_some(Json::Literal)
+            }
+            _ => {
+                let number: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c))).collect();
+                number.parse().ok().map(Json::Number)
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use crate::{hash, project, settings};
+
+/// Peaks per second of source audio in the base waveform level; fine enough to see
+/// mouth clicks and plosives when the timeline is zoomed all the way in.
+pub const WAVEFORM_PEAKS_PER_SEC: u32 = 2000;
+const WAVEFORM_SAMPLE_RATE: u32 = 8000;
+const PYRAMID_FACTOR: usize = 4;
+const PROXY_HEIGHT: u32 = 540;
+
+pub enum MediaJob {
+    Waveform(PathBuf),
+    Thumbnail { path: PathBuf, source_ms: u32 },
+    Proxy(PathBuf),
+    CaptureDate(PathBuf),
+}
+
+pub enum MediaJobResult {
+    Waveform { path: PathBuf, waveform: Option<Waveform> },
+    Thumbnail { path: PathBuf, image: Option<egui::ColorImage> },
+    Proxy { path: PathBuf, proxy: Option<PathBuf> },
+    CaptureDate { path: PathBuf, date: Option<String> },
+}
+
+/// Background worker for slow per-file analysis (waveform peaks, thumbnails, proxies).
+/// It can be paused so it does not compete with playback or export for CPU and disk.
+pub struct MediaJobs {
+    job_sender: mpsc::Sender<MediaJob>,
+    pub result_receiver: mpsc::Receiver<MediaJobResult>,
+    paused: Arc<AtomicBool>,
+    _thread_handle: thread::JoinHandle<()>,
+}
+
+impl MediaJobs {
+    pub fn new(ctx: egui::Context) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<MediaJob>();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        let paused_flag = paused.clone();
+
+        let thread_handle = thread::spawn(move || {
+            // exits once the app drops its sender
+            while let Ok(job) = job_receiver.recv() {
+                // queued jobs wait; a running waveform stops reading and a running proxy restarts
+                while paused_flag.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(100));
+                }
+                let result = match job {
+                    MediaJob::Waveform(path) => {
+                        println!("media jobs: waveform {}", path.display());
+                        let cache_file = peaks_cache_file(&path);
+                        let peaks = match cache_file.as_ref().and_then(|f| std::fs::read(f).ok()) {
+                            Some(cached) => Some(cached),
+                            None => {
+                                let peaks = extract_peaks(&path, &paused_flag);
+                                if let (Some(file), Some(peaks)) = (&cache_file, &peaks) {
+                                    let _ = file.parent().map(std::fs::create_dir_all);
+                                    let _ = std::fs::write(file, peaks);
+                                }
+                                peaks
+                            }
+                        };
+                        MediaJobResult::Waveform { path, waveform: peaks.map(Waveform::new) }
+                    }
+                    MediaJob::Thumbnail { path, source_ms } => {
+                        println!("media jobs: thumbnail {}", path.display());
+                        let image = project::capture_poster(&path, source_ms)
+                            .and_then(|png| project::poster_to_image(&png));
+                        MediaJobResult::Thumbnail { path, image }
+                    }
+                    MediaJob::Proxy(path) => {
+                        println!("media jobs: proxy {}", path.display());
+                        let proxy = proxy_file(&path).filter(|file| make_proxy(&path, file, &paused_flag));
+                        MediaJobResult::Proxy { path, proxy }
+                    }
+                    MediaJob::CaptureDate(path) => {
+                        let date = capture_date(&path);
+                        MediaJobResult::CaptureDate { path, date }
+                    }
+                };
+                if result_sender.send(result).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            job_sender,
+            result_receiver,
+            paused,
+            _thread_handle: thread_handle,
+        }
+    }
+
+    pub fn send_job(&self, job: MediaJob) {
+        let _ = self.job_sender.send(job);
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+}
+
+/// Cache entry name keyed by path, size and modification time so replaced files are re-read.
+fn cache_key(path: &PathBuf) -> Option<String> {
+    let meta = std::fs::metadata(path).ok()?;
+    let key = format!("{}|{}|{:?}", path.display(), meta.len(), meta.modified().ok());
+    Some(hash::sha256_hex(key.as_bytes()))
+}
+
+// only the base level is cached; the rate is in the name so a change re-extracts
+fn peaks_cache_file(path: &PathBuf) -> Option<PathBuf> {
+    Some(settings::cache_dir().join("waveforms").join(format!("{}-{}.peaks", cache_key(path)?, WAVEFORM_PEAKS_PER_SEC)))
+}
+
+/// Where the low-resolution preview copy of `path` lives (whether or not it has been made yet).
+pub fn proxy_file(path: &PathBuf) -> Option<PathBuf> {
+    Some(settings::cache_dir().join("proxies").join(format!("{}.mp4", cache_key(path)?)))
+}
+
+/// Transcodes a small all-intra H.264 copy that seeks instantly; audio is dropped
+/// since the preview does not play it. A pause kills the transcode and it starts over
+/// once resumed, so playback and export never share the CPU with it.
+fn make_proxy(path: &PathBuf, proxy: &PathBuf, paused: &AtomicBool) -> bool {
+    let _ = proxy.parent().map(std::fs::create_dir_all);
+    let partial = proxy.with_extension("partial.mp4");
+    let ok = loop {
+        while paused.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        let Ok(mut child) = Command::new("ffmpeg")
+            .arg("-y")
+            .arg("-i").arg(path)
+            .arg("-an")
+            .arg("-vf").arg(format!("scale=-2:{}", PROXY_HEIGHT))
+            .arg("-c:v").arg("libx264")
+            .arg("-preset").arg("veryfast")
+            .arg("-g").arg("1")
+            .arg("-crf").arg("28")
+            .arg(&partial)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            break false;
+        };
+        let finished = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status.success()),
+                Ok(None) if paused.load(Ordering::Relaxed) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break None;
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(100)),
+                Err(_) => break Some(false),
+            }
+        };
+        if let Some(success) = finished {
+            break success;
+        }
+    };
+    // renamed into place only once complete, so a half-written proxy is never picked up
+    let ok = ok && std::fs::rename(&partial, proxy).is_ok();
+    if !ok {
+        let _ = std::fs::remove_file(&partial);
+    }
+    ok
+}
+
+/// Recording date from the container's creation_time tag, as "YYYY-MM-DD HH:MM".
+fn capture_date(path: &PathBuf) -> Option<String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format_tags=creation_time", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    // e.g. 2024-05-17T09:41:22.000000Z
+    let text = String::from_utf8_lossy(&output.stdout);
+    let stamp = text.lines().next()?.trim();
+    stamp.get(..16).map(|minutes| minutes.replace('T', " "))
+}
+
+/// Peak pyramid: level 0 is the extracted peaks, each level above keeps the loudest of every
+/// `PYRAMID_FACTOR` peaks below it, so any zoom reads about one peak per pixel.
+pub struct Waveform {
+    levels: Vec<Vec<u8>>,
+}
+
+impl Waveform {
+    fn new(base: Vec<u8>) -> Self {
+        let mut levels = vec![base];
+        while let Some(last) = levels.last().filter(|l| l.len() > PYRAMID_FACTOR) {
+            let next = last.chunks(PYRAMID_FACTOR).map(|c| c.iter().copied().max().unwrap_or(0)).collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Loudest peak between two source times, from the coarsest level that still resolves the span.
+    pub fn peak(&self, from_ms: f32, to_ms: f32) -> u8 {
+        let span = (to_ms - from_ms) * WAVEFORM_PEAKS_PER_SEC as f32 / 1000.0;
+        let mut level = 0;
+        while level + 1 < self.levels.len() && (PYRAMID_FACTOR.pow(level as u32 + 1) as f32) <= span {
+            level += 1;
+        }
+        let ms_per_peak = PYRAMID_FACTOR.pow(level as u32) as f32 * 1000.0 / WAVEFORM_PEAKS_PER_SEC as f32;
+        let peaks = &self.levels[level];
+        let from = (from_ms / ms_per_peak) as usize;
+        let to = ((to_ms / ms_per_peak).ceil() as usize).max(from + 1).min(peaks.len());
+        peaks.get(from..to).and_then(|p| p.iter().max()).copied().unwrap_or(0)
+    }
+}
+
+/// Decodes the audio as mono and keeps the absolute peak of every 1/`WAVEFORM_PEAKS_PER_SEC` s, scaled to 0..=255.
+/// Stalls while `paused` is set; ffmpeg then blocks on the full pipe, so the decode pauses too.
+fn extract_peaks(path: &PathBuf, paused: &AtomicBool) -> Option<Vec<u8>> {
+    let mut child = Command::new("ffmpeg")
+        .arg("-i").arg(path)
+        .arg("-vn")
+        .arg("-ac").arg("1")
+        .arg("-ar").arg(WAVEFORM_SAMPLE_RATE.to_string())
+        .arg("-f").arg("s16le")
+        .arg("-")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+
+    let samples_per_peak = (WAVEFORM_SAMPLE_RATE / WAVEFORM_PEAKS_PER_SEC) as usize;
+    let mut buffer = vec![0u8; samples_per_peak * 2];
+    let mut peaks = Vec::new();
+    while stdout.read_exact(&mut buffer).is_ok() {
+        while paused.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        let peak = buffer.chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]).unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        peaks.push((peak >> 7).min(255) as u8);
+    }
+    let _ = child.wait();
+
+    (!peaks.is_empty()).then_some(peaks)
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Desktop notification for a finished export. Runs on its own thread because the Linux
+/// notifier blocks until the notification is clicked or dismissed.
+pub fn export_finished(output: &Path, success: bool) {
+    let output = output.to_path_buf();
+    let name = output.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let (title, body) = if success {
+        ("Export finished", format!("{} is ready", name))
+    } else {
+        ("Export failed", format!("{} could not be exported", name))
+    };
+
+    thread::spawn(move || {
+        if cfg!(target_os = "linux") {
+            // libnotify prints the chosen action to stdout when the notification is clicked
+            let mut cmd = Command::new("notify-send");
+            cmd.args(["--app-name", "Video Editor", "--wait"]);
+            if success {
+                cmd.args(["--action", "default=Open", "--action", "open=Open"]);
+            }
+            let Ok(result) = cmd.arg(title).arg(&body).stderr(Stdio::null()).output() else { return };
+            if success && !String::from_utf8_lossy(&result.stdout).trim().is_empty() {
+                open_path(&output);
+            }
+        } else if cfg!(target_os = "macos") {
+            let script = format!("display notification {:?} with title {:?}", body, title);
+            let _ = Command::new("osascript").arg("-e").arg(script).status();
+        } else if cfg!(target_os = "windows") {
+            let script = format!(
+                "Add-Type -AssemblyName System.Windows.Forms; \
+                 $n = New-Object System.Windows.Forms.NotifyIcon; \
+                 $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+                 $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); Start-Sleep -Seconds 10; $n.Dispose()",
+                title,
+                body.replace('\'', "''")
+            );
+            let _ = Command::new("powershell").args(["-NoProfile", "-WindowStyle", "Hidden", "-Command"]).arg(script).status();
+        }
+    });
+}
+
+/// Opens a file with the desktop's default application.
+pub fn open_path(path: &Path) {
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let _ = Command::new(opener).arg(path).spawn();
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::{VideoClip, TIMELINE_FPS};
+
+/// End card appended by "Append Outro": a title on a solid background with an optional
+/// logo, faded in from the last frame of the timeline. Saved with the project.
+#[derive(Clone, PartialEq)]
+pub struct OutroTemplate {
+    pub title: String,
+    pub logo: Option<PathBuf>,
+    pub background: [u8; 3],
+    pub duration_ms: u32,
+    pub fade_ms: u32,
+}
+
+impl Default for OutroTemplate {
+    fn default() -> Self {
+        Self { title: String::new(), logo: None, background: [0, 0, 0], duration_ms: 5000, fade_ms: 1000 }
+    }
+}
+
+/// Renders on a worker; the result arrives once on the returned channel.
+pub fn start(ctx: &egui::Context, template: OutroTemplate, last_clip: Option<VideoClip>, output: PathBuf) -> mpsc::Receiver<Result<(), &'static str>> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let _ = sender.send(render(&template, last_clip.as_ref(), &output));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+/// Renders the outro to `output` as an ordinary 1080p30 clip with silent audio, starting on
+/// the last frame of `last_clip` (black when there is none) so it joins the cut seamlessly.
+fn render(template: &OutroTemplate, last_clip: Option<&VideoClip>, output: &Path) -> Result<(), &'static str> {
+    let secs = |ms: u32| format!("{:.3}", ms as f32 / 1000.0);
+    let fade_ms = template.fade_ms.min(template.duration_ms);
+    let mut cmd = Command::new("ffmpeg");
+    cmd.arg("-y");
+
+    // input 0: the frame to fade from
+    let from_filters = match last_clip {
+        Some(clip) if clip.generator.is_none() => {
+            // one frame before the out point is the last one shown
+            let frame_ms = (1000.0 / TIMELINE_FPS).ceil() as u32;
+            let last_frame_ms = clip.trim_end.saturating_sub(frame_ms).max(clip.trim_start);
+            cmd.arg("-ss").arg(secs(last_frame_ms)).arg("-i").arg(&clip.path);
+            clip.video_filters()
+        }
+        Some(VideoClip { generator: Some([r, g, b]), .. }) => {
+            cmd.arg("-f").arg("lavfi").arg("-i").arg(format!("color=c=0x{:02x}{:02x}{:02x}:s=1920x1080:r=30", r, g, b));
+            String::new()
+        }
+        _ => {
+            cmd.arg("-f").arg("lavfi").arg("-i").arg("color=c=black:s=1920x1080:r=30");
+            String::new()
+        }
+    };
+    // input 1: the card background, input 2: silence, input 3: the logo
+    let [r, g, b] = template.background;
+    cmd.arg("-f").arg("lavfi").arg("-i").arg(format!("color=c=0x{:02x}{:02x}{:02x}:s=1920x1080:r=30:d={}", r, g, b, secs(template.duration_ms)))
+        .arg("-f").arg("lavfi").arg("-i").arg("anullsrc=r=48000:cl=stereo");
+    if let Some(logo) = &template.logo {
+        cmd.arg("-loop").arg("1").arg("-i").arg(logo);
+    }
+
+    let mut graph = format!(
+        "[0:v]{}scale=1920:1080:force_original_aspect_ratio=decrease,pad=1920:1080:(ow-iw)/2:(oh-ih)/2,setsar=1,fps=30,format=yuv420p,trim=end_frame=1,tpad=stop_mode=clone:stop_duration={}[from];",
+        from_filters, secs(fade_ms)
+    );
+    // the title goes through a file so it needs no filtergraph escaping
+    let title_file = output.with_extension("title.txt");
+    let mut card = "[1:v]".to_string();
+    if !template.title.trim().is_empty() {
+        fs::write(&title_file, template.title.trim()).map_err(|_| "Error writing outro title")?;
+        graph.push_str(&format!(
+            "{}drawtext=textfile='{}':expansion=none:fontcolor=white:fontsize=96:x=(w-tw)/2:y=(h-th)/2-120[titled];",
+            card, filter_path(&title_file)
+        ));
+        card = "[titled]".to_string();
+    }
+    if template.logo.is_some() {
+        graph.push_str(&format!("[3:v]scale=-1:240[logo];{}[logo]overlay=(W-w)/2:H-h-140:shortest=1[card];", card));
+        card = "[card]".to_string();
+    }
+    // xfade needs both sides in the same pixel format
+    graph.push_str(&format!("{}format=yuv420p[to];[from][to]xfade=transition=fade:duration={}:offset=0[outv]", card, secs(fade_ms)));
+
+    let status = cmd.arg("-filter_complex").arg(graph)
+        .arg("-map").arg("[outv]")
+        .arg("-map").arg("2:a")
+        .arg("-t").arg(secs(template.duration_ms))
+        .arg("-c:v").arg("libx264")
+        .arg("-c:a").arg("aac")
+        .arg(output)
+        .status();
+    let _ = fs::remove_file(&title_file);
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(_) => Err("Error rendering the outro"),
+        Err(_) => Err("Error running ffmpeg"),
+    }
+}
+
+/// Path for a quoted filter option: forward slashes, the drive colon and any quotes escaped.
+fn filter_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").replace(':', "\\:").replace('\'', "'\\''")
+}
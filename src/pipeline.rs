@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Export pipelines: several deliverables from one render, described in a TOML file.
+// Only the subset needed here is read: `[[output]]` tables of string, integer and boolean keys.
+//
+//   [[output]]
+//   suffix = "master"                   # film.master.mov
+//   extension = "mov"                   # default "mp4"
+//   options = "-c:v prores_ks -profile:v 3 -c:a pcm_s24le"
+//
+//   [[output]]
+//   suffix = "preview"
+//   height = 720                        # scaled from the 1080p timeline, default unscaled
+//   options = "-c:v libx264 -crf 26 -preset veryfast -c:a aac -b:a 128k"
+//
+//   [[output]]
+//   suffix = "audio"
+//   extension = "wav"
+//   video = false                       # video / audio default to true
+
+pub struct PipelineOutput {
+    pub suffix: String,
+    pub extension: String,
+    pub video: bool,
+    pub audio: bool,
+    pub height: Option<u32>,
+    pub options: Vec<String>, // encoder arguments, passed to ffmpeg as given
+}
+
+impl Default for PipelineOutput {
+    fn default() -> Self {
+        Self { suffix: String::new(), extension: "mp4".to_string(), video: true, audio: true, height: None, options: Vec::new() }
+    }
+}
+
+impl PipelineOutput {
+    /// `<dir>/<base stem>.<suffix>.<extension>`
+    pub fn path(&self, base: &Path) -> PathBuf {
+        let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+        base.with_file_name(format!("{}.{}.{}", stem, self.suffix, self.extension))
+    }
+}
+
+pub fn load(path: &Path) -> Result<Vec<PipelineOutput>, &'static str> {
+    let text = fs::read_to_string(path).map_err(|_| "Error reading pipeline file")?;
+    let mut outputs: Vec<PipelineOutput> = Vec::new();
+    for line in text.lines() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "[[output]]" {
+            outputs.push(PipelineOutput::default());
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or("Malformed line in pipeline file")?;
+        let output = outputs.last_mut().ok_or("Pipeline keys must follow an [[output]] header")?;
+        let value = value.trim();
+        match key.trim() {
+            "suffix" => output.suffix = string(value)?,
+            "extension" => output.extension = string(value)?,
+            "options" => output.options = string(value)?.split_whitespace().map(String::from).collect(),
+            "video" => output.video = boolean(value)?,
+            "audio" => output.audio = boolean(value)?,
+            "height" => output.height = Some(value.parse().map_err(|_| "Pipeline height must be a number")?),
+            _ => return Err("Unknown key in pipeline file"),
+        }
+    }
+
+    if outputs.is_empty() {
+        return Err("Pipeline file has no [[output]] entries");
+    }
+    for (i, output) in outputs.iter().enumerate() {
+        if output.suffix.is_empty() {
+            return Err("Every pipeline output needs a suffix");
+        }
+        if !output.video && !output.audio {
+            return Err("A pipeline output must keep video or audio");
+        }
+        if outputs[..i].iter().any(|o| o.suffix == output.suffix && o.extension == output.extension) {
+            return Err("Two pipeline outputs would write the same file");
+        }
+    }
+    Ok(outputs)
+}
+
+/// Turns a single-output timeline render into one that decodes once and writes every
+/// pipeline output. `timeline_args` must render both `[outv]` and `[outa]` as needed; its
+/// mapping and output arguments after the filter graph are replaced.
+pub fn build_args(timeline_args: &[String], outputs: &[PipelineOutput], base: &Path) -> Result<Vec<String>, &'static str> {
+    let graph_at = timeline_args.iter().position(|a| a == "-filter_complex").ok_or("Render has no filter graph")?;
+    let mut args = timeline_args[..graph_at].to_vec();
+    let mut graph = timeline_args.get(graph_at + 1).ok_or("Render has no filter graph")?.clone();
+
+    let video_count = outputs.iter().filter(|o| o.video).count();
+    let audio_count = outputs.iter().filter(|o| o.audio).count();
+    if video_count > 0 {
+        graph.push_str(&format!(";[outv]split={}{}", video_count, (0..video_count).map(|i| format!("[pv{}]", i)).collect::<String>()));
+    }
+    if audio_count > 0 {
+        graph.push_str(&format!(";[outa]asplit={}{}", audio_count, (0..audio_count).map(|i| format!("[pa{}]", i)).collect::<String>()));
+    }
+    for (i, output) in outputs.iter().filter(|o| o.video).enumerate() {
+        if let Some(height) = output.height {
+            graph.push_str(&format!(";[pv{}]scale=-2:{}[pv{}s]", i, height, i));
+        }
+    }
+    args.push("-filter_complex".to_string());
+    args.push(graph);
+
+    let (mut video, mut audio) = (0, 0);
+    for output in outputs {
+        if output.video {
+            let scaled = if output.height.is_some() { "s" } else { "" };
+            args.extend(["-map".to_string(), format!("[pv{}{}]", video, scaled)]);
+            video += 1;
+        }
+        if output.audio {
+            args.extend(["-map".to_string(), format!("[pa{}]", audio)]);
+            audio += 1;
+        }
+        args.extend(output.options.iter().cloned());
+        args.push(output.path(base).to_string_lossy().into_owned());
+    }
+    Ok(args)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn string(value: &str) -> Result<String, &'static str> {
+    let inner = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or("Pipeline strings must be quoted")?;
+    Ok(inner.replace("\\\"", "\"").replace("\\\\", "\\"))
+}
+
+fn boolean(value: &str) -> Result<bool, &'static str> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err("Pipeline flags must be true or false"),
+    }
+}
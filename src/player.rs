@@ -4,6 +4,8 @@ use std::io::{Read, BufReader};
 use std::thread;
 use std::sync::mpsc;
 
+use crate::TIMELINE_FPS;
+
 pub const PREVIEW_WIDTH: u32 = 640;
 pub const PREVIEW_HEIGHT: u32 = 360;
 
@@ -13,6 +15,7 @@ pub enum PlayerCommand {
         path: PathBuf,
         trim_start_ms: u32,
         trim_end_ms: u32,
+        filters: String, // prepended to the preview scale, e.g. "hflip,"
     },
     StartPlayback {
         timestamp_ms: u32, // relative to trimmed clip
@@ -21,9 +24,74 @@ pub enum PlayerCommand {
     Seek {
         timestamp_ms: u32, // scrubbing
     },
+    /// Moves the out point of the loaded clip without restarting playback; takes effect
+    /// as long as the new end has not been played past yet.
+    SetTrimEnd {
+        trim_end_ms: u32,
+    },
+    SetProgramOutput(Option<ProgramOutput>),
+    SetLowPower(bool), // takes effect from the next Seek/StartPlayback
     Stop,
 }
 
+/// Full-quality program feed sent to an external display while playing.
+#[derive(Clone, PartialEq)]
+pub struct ProgramOutput {
+    pub format: String, // ffmpeg output device, e.g. "decklink" or "libndi_newtek"
+    pub device: String,
+}
+
+pub const PROGRAM_OUTPUT_FORMATS: &[&str] = &["decklink", "libndi_newtek"];
+
+fn spawn_program_output(output: &ProgramOutput, path: &PathBuf, start_secs: f32, end_secs: f32, filters: &str) -> Option<Child> {
+    let mut cmd = Command::new("ffmpeg");
+    cmd.arg("-re")
+        .arg("-ss").arg(format!("{:.3}", start_secs))
+        .arg("-to").arg(format!("{:.3}", end_secs))
+        .arg("-i").arg(path)
+        .arg("-vf").arg(format!(
+            "{}scale=1920:1080:force_original_aspect_ratio=decrease,pad=1920:1080:(ow-iw)/2:(oh-ih)/2",
+            filters
+        ))
+        .arg("-pix_fmt").arg("uyvy422")
+        .arg("-c:a").arg("pcm_s16le").arg("-ar").arg("48000")
+        .arg("-f").arg(&output.format)
+        .arg(&output.device)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    match cmd.spawn() {
+        Ok(child) => Some(child),
+        Err(e) => {
+            eprintln!("player: Failed to start program output: {}", e);
+            None
+        }
+    }
+}
+
+/// Decodes the single frame at `source_ms`, scaled to `size`.
+pub fn grab_frame(path: &PathBuf, source_ms: u32, filters: &str, size: [u32; 2]) -> Option<egui::ColorImage> {
+    let mut child = Command::new("ffmpeg")
+        .arg("-ss").arg(format!("{:.3}", source_ms as f32 / 1000.0))
+        .arg("-i").arg(path)
+        .arg("-frames:v").arg("1")
+        .arg("-vf").arg(format!("{}scale={}:{}", filters, size[0], size[1]))
+        .arg("-pix_fmt").arg("rgba")
+        .arg("-f").arg("rawvideo")
+        .arg("-")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let mut buffer = vec![0u8; (size[0] * size[1] * 4) as usize];
+    let read = child.stdout.take()?.read_exact(&mut buffer);
+    let _ = child.wait();
+    read.ok()?;
+    Some(egui::ColorImage::from_rgba_unmultiplied([size[0] as usize, size[1] as usize], &buffer))
+}
+
 pub struct DecodedFrame {
     pub image: egui::ColorImage,
     _timestamp_ms: u32,
@@ -49,24 +117,46 @@ impl VideoPlayer {
         let thread_handle = thread::spawn(move || {
             let mut last_frame_time = std::time::Instant::now();
             const TARGET_FRAME_TIME: std::time::Duration = std::time::Duration::from_millis(33);
+            const LOW_POWER_FRAME_TIME: std::time::Duration = std::time::Duration::from_millis(66);
+            const LOW_POWER_FPS: f32 = 15.0;
+
+            // low power mode decodes at half size and half rate
+            let mut low_power = false;
+            let mut decode_width = PREVIEW_WIDTH;
+            let mut decode_height = PREVIEW_HEIGHT;
+            let mut frame_time = TARGET_FRAME_TIME;
 
             let mut current_clip_path: Option<PathBuf> = None;
             let mut current_clip_trim_start_ms: u32 = 0;
             let mut current_clip_trim_end_ms: u32 = 0;
+            let mut current_clip_filters = String::new();
             
             // ffmpeg subprocess
             let mut playback_process: Option<Child> = None;
             let mut playback_stdout: Option<BufReader<std::process::ChildStdout>> = None;
             let mut is_playing = false;
+            // source time of the next frame: the seek point plus the frames read so far, which
+            // ffmpeg is made to emit at a fixed rate whatever the source rate is
+            let mut playback_start_ms: u32 = 0;
+            let mut frames_read: u32 = 0;
+            let mut playback_fps = TIMELINE_FPS;
+
+            let mut program_output: Option<ProgramOutput> = None;
+            let mut output_process: Option<Child> = None;
 
             loop {
                 if let Ok(cmd) = command_receiver.try_recv() {
                     match cmd {
-                        PlayerCommand::LoadClip { path, trim_start_ms, trim_end_ms } => {
+                        PlayerCommand::LoadClip { path, trim_start_ms, trim_end_ms, filters } => {
                             println!("main -> player: LoadClip");
                             current_clip_path = Some(path.clone());
                             current_clip_trim_start_ms = trim_start_ms;
                             current_clip_trim_end_ms = trim_end_ms;
+                            current_clip_filters = filters;
+                            if let Some(mut child) = output_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
+                            }
                             
                             if let Some(mut child) = playback_process.take() {
                                 let _ = child.kill();
@@ -78,40 +168,63 @@ impl VideoPlayer {
                         PlayerCommand::StartPlayback { timestamp_ms } => {
                             println!("main -> player: StartPlayBack");
                             // dont play twice
-                            if !is_playing {
-                                if let Some(path) = &current_clip_path {
-                                    if let Some(mut child) = playback_process.take() {
-                                        // kill existing process
-                                        let _ = child.kill();
-                                        let _ = child.wait();
-                                    }
-                                    
-                                    let ffmpeg_seek_time_secs = (current_clip_trim_start_ms + timestamp_ms) as f32 / 1000.0;
-                                    let mut cmd = Command::new("ffmpeg");
-                                    cmd.arg("-ss").arg(format!("{:.3}", ffmpeg_seek_time_secs))
-                                        .arg("-to").arg(format!("{:.3}", current_clip_trim_end_ms as f32 / 1000.0))
-                                        .arg("-i").arg(path)
-                                        .arg("-vf").arg(format!("scale={}:{}", PREVIEW_WIDTH, PREVIEW_HEIGHT))
-                                        .arg("-pix_fmt").arg("rgba")
-                                        .arg("-f").arg("rawvideo")
-                                        .arg("-") // continuous stdout
-                                        .stderr(Stdio::null());
-
-                                    println!("player: calling ffmpeg");
-
-                                    match cmd.stdout(Stdio::piped()).spawn() {
-                                        Ok(mut child) => {
-                                            playback_stdout = child.stdout.take().map(|s| BufReader::new(s));
-                                            playback_process = Some(child);
-                                            is_playing = true;
-                                            println!("player: started persistent playback of clip starting at {:.3}s", ffmpeg_seek_time_secs);
+                            if !is_playing
+                                && let Some(path) = &current_clip_path
+                            {
+                                if let Some(mut child) = playback_process.take() {
+                                    // kill existing process
+                                    let _ = child.kill();
+                                    let _ = child.wait();
+                                }
+                                
+                                let ffmpeg_seek_time_secs = (current_clip_trim_start_ms + timestamp_ms) as f32 / 1000.0;
+                                playback_start_ms = current_clip_trim_start_ms + timestamp_ms;
+                                frames_read = 0;
+                                playback_fps = if low_power { LOW_POWER_FPS } else { TIMELINE_FPS };
+                                // no -to: decoding runs on and the out point is checked per frame,
+                                // so it can move while playing (SetTrimEnd)
+                                let mut cmd = Command::new("ffmpeg");
+                                cmd.arg("-ss").arg(format!("{:.3}", ffmpeg_seek_time_secs))
+                                    .arg("-i").arg(path);
+                                if low_power {
+                                    // drop frames at the source so reading at the slower tick keeps realtime
+                                    cmd.arg("-vf").arg(format!("{}scale={}:{}", current_clip_filters, decode_width, decode_height))
+                                        .arg("-r").arg(LOW_POWER_FPS.to_string());
+                                } else {
+                                    cmd.arg("-vf").arg(format!("{}fps={},scale={}:{}", current_clip_filters, TIMELINE_FPS, decode_width, decode_height));
+                                }
+                                cmd.arg("-pix_fmt").arg("rgba");
+                                cmd.arg("-f").arg("rawvideo")
+                                    .arg("-") // continuous stdout
+                                    .stderr(Stdio::null());
+
+                                println!("player: calling ffmpeg");
+
+                                match cmd.stdout(Stdio::piped()).spawn() {
+                                    Ok(mut child) => {
+                                        playback_stdout = child.stdout.take().map(BufReader::new);
+                                        playback_process = Some(child);
+                                        is_playing = true;
+                                        if let Some(output) = &program_output {
+                                            output_process = spawn_program_output(
+                                                output,
+                                                path,
+                                                ffmpeg_seek_time_secs,
+                                                current_clip_trim_end_ms as f32 / 1000.0,
+                                                &current_clip_filters,
+                                            );
                                         }
-                                        Err(e) => eprintln!("player: Failed to start playback: {}", e),
+                                        println!("player: started persistent playback of clip starting at {:.3}s", ffmpeg_seek_time_secs);
                                     }
+                                    Err(e) => eprintln!("player: Failed to start playback: {}", e),
                                 }
                             }
                         }
                         PlayerCommand::StopPlayback => {
+                            if let Some(mut child) = output_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
+                            }
                             if let Some(mut child) = playback_process.take() {
                                 let _ = child.kill();
                                 let _ = child.wait();
@@ -122,43 +235,64 @@ impl VideoPlayer {
                         }
                         PlayerCommand::Seek { timestamp_ms } => {
                             println!("main -> player: Seek");
-                            if !is_playing { // scrubbing
-                                if let Some(path) = &current_clip_path {
-                                    let ffmpeg_seek_time_secs = (current_clip_trim_start_ms + timestamp_ms) as f32 / 1000.0;
-                                    
-                                    let mut cmd = Command::new("ffmpeg");
-                                    cmd.arg("-ss").arg(format!("{:.3}", ffmpeg_seek_time_secs))
-                                       .arg("-i").arg(path)
-                                       .arg("-frames:v").arg("1")
-                                       .arg("-vf").arg(format!("scale={}:{}", PREVIEW_WIDTH, PREVIEW_HEIGHT))
-                                       .arg("-pix_fmt").arg("rgba")
-                                       .arg("-f").arg("rawvideo")
-                                       .arg("-")
-                                       .stderr(Stdio::null());
-
-                                    if let Ok(mut child) = cmd.stdout(Stdio::piped()).spawn() {
-                                        if let Some(mut stdout) = child.stdout.take() {
-                                            let frame_size = (PREVIEW_WIDTH * PREVIEW_HEIGHT * 4) as usize;
-                                            let mut buffer = vec![0u8; frame_size];
-                                            if stdout.read_exact(&mut buffer).is_ok() {
-                                                let image = egui::ColorImage::from_rgba_unmultiplied(
-                                                    [PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize],
-                                                    &buffer,
-                                                );
-                                                let _ = frame_sender.send(DecodedFrame { 
-                                                    image, 
-                                                    _timestamp_ms: timestamp_ms 
-                                                });
-                                                egui_ctx_clone.request_repaint();
-                                            }
-                                        }
-                                        let _ = child.wait();
-                                    }
-                                }
+                            if !is_playing // scrubbing
+                                && let Some(path) = &current_clip_path
+                                && let Some(image) = grab_frame(
+                                    path,
+                                    current_clip_trim_start_ms + timestamp_ms,
+                                    &current_clip_filters,
+                                    [decode_width, decode_height],
+                                )
+                            {
+                                let _ = frame_sender.send(DecodedFrame { 
+                                    image, 
+                                    _timestamp_ms: timestamp_ms 
+                                });
+                                egui_ctx_clone.request_repaint();
+                            }
+                        }
+                        PlayerCommand::SetLowPower(enabled) => {
+                            low_power = enabled;
+                            (decode_width, decode_height) = if enabled {
+                                (PREVIEW_WIDTH / 2, PREVIEW_HEIGHT / 2)
+                            } else {
+                                (PREVIEW_WIDTH, PREVIEW_HEIGHT)
+                            };
+                            frame_time = if enabled { LOW_POWER_FRAME_TIME } else { TARGET_FRAME_TIME };
+                        }
+                        PlayerCommand::SetTrimEnd { trim_end_ms } => {
+                            current_clip_trim_end_ms = trim_end_ms;
+                        }
+                        PlayerCommand::SetProgramOutput(output) => {
+                            if output == program_output {
+                                continue;
+                            }
+                            if let Some(mut child) = output_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
                             }
+                            // while playing, the new output picks up from the frame being shown
+                            if is_playing
+                                && let Some(output) = &output
+                                && let Some(path) = &current_clip_path
+                            {
+                                let position_ms = playback_start_ms as f32 + frames_read as f32 * 1000.0 / playback_fps;
+                                output_process = spawn_program_output(
+                                    output,
+                                    path,
+                                    position_ms / 1000.0,
+                                    current_clip_trim_end_ms as f32 / 1000.0,
+                                    &current_clip_filters,
+                                );
+                            }
+                            program_output = output;
                         }
                         PlayerCommand::Stop => {
                             // Clean shutdown
+                            if let Some(mut child) = output_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
+                            }
                             if let Some(mut child) = playback_process.take() {
                                 let _ = child.kill();
                                 let _ = child.wait();
@@ -169,42 +303,50 @@ impl VideoPlayer {
                     continue; // skip for this tick
                 }
 
-                if is_playing {
-                    if let Some(stdout) = &mut playback_stdout {
-                        let elapsed = last_frame_time.elapsed();
-                        if elapsed < TARGET_FRAME_TIME {
-                            thread::sleep(TARGET_FRAME_TIME - elapsed);
+                if is_playing
+                    && let Some(stdout) = &mut playback_stdout
+                {
+                    let elapsed = last_frame_time.elapsed();
+                    if elapsed < frame_time {
+                        thread::sleep(frame_time - elapsed);
+                    }
+                    last_frame_time = std::time::Instant::now();
+                    let frame_size = (decode_width * decode_height * 4) as usize;
+                    let mut buffer = vec![0u8; frame_size];
+
+                    let playback_position_ms = playback_start_ms as f32 + frames_read as f32 * 1000.0 / playback_fps;
+                    let past_out_point = playback_position_ms >= current_clip_trim_end_ms as f32;
+                    match stdout.read_exact(&mut buffer).ok().filter(|_| !past_out_point) {
+                        Some(_) => {
+                            frames_read += 1;
+                            let image = egui::ColorImage::from_rgba_unmultiplied(
+                                [decode_width as usize, decode_height as usize],
+                                &buffer,
+                            );
+                            let _ = frame_sender.send(DecodedFrame { 
+                                image, 
+                                _timestamp_ms: 0
+                            });
+                            egui_ctx_clone.request_repaint();
                         }
-                        last_frame_time = std::time::Instant::now();
-                        let frame_size = (PREVIEW_WIDTH * PREVIEW_HEIGHT * 4) as usize;
-                        let mut buffer = vec![0u8; frame_size];
-                        
-                        match stdout.read_exact(&mut buffer) {
-                            Ok(_) => {
-                                let image = egui::ColorImage::from_rgba_unmultiplied(
-                                    [PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize],
-                                    &buffer,
-                                );
-                                let _ = frame_sender.send(DecodedFrame { 
-                                    image, 
-                                    _timestamp_ms: 0
-                                });
-                                egui_ctx_clone.request_repaint();
+                        None => { // playback finished
+                            if let Some(mut child) = output_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
                             }
-                            Err(_) => { // playback finished
-                                if let Some(mut child) = playback_process.take() {
-                                    let _ = child.wait();
-                                }
-                                playback_stdout = None;
-                                is_playing = false;
-                                println!("player -> main: PlaybackEnded");
-                                
-                                let _ = frame_sender.send(DecodedFrame { 
-                                    image: egui::ColorImage::filled([PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize], egui::Color32::BLACK),
-                                    _timestamp_ms: 0 
-                                });
-                                let _ = playback_ended_sender.send(PlaybackEnded);
+                            if let Some(mut child) = playback_process.take() {
+                                let _ = child.kill();
+                                let _ = child.wait();
                             }
+                            playback_stdout = None;
+                            is_playing = false;
+                            println!("player -> main: PlaybackEnded");
+                            
+                            let _ = frame_sender.send(DecodedFrame { 
+                                image: egui::ColorImage::filled([PREVIEW_WIDTH as usize, PREVIEW_HEIGHT as usize], egui::Color32::BLACK),
+                                _timestamp_ms: 0 
+                            });
+                            let _ = playback_ended_sender.send(PlaybackEnded);
                         }
                     }
                 }
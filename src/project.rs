@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::letterbox::Crop;
+use crate::outro::OutroTemplate;
+use crate::{settings, AltAudio, AudioTracks, ColorGrade, Marker, MediaItem, Stem, VideoClip};
+
+// project file layout (plain text, one record per line):
+//   videoedit-project 1
+//   info <content duration ms> <clip count>
+//   poster <png bytes as hex>            (optional)
+//   media <duration> <path>             (media bin entry)
+//   mediastamp <size> <mtime>           (source file when probed, applies to the preceding media)
+//   clip <timeline_start> <trim_start> <trim_end> <duration> <path>
+//   set <key> <value>                   (per-clip option, applies to the preceding clip)
+//   marker <time> <note>
+//   audiolang <code>                    (timeline mix language, optional)
+//   altaudio <code or -> <path>         (alternate-language audio stream)
+//   cutfade <frames>                    (audio crossfade at every cut, optional)
+//   outro <duration> <fade> <rrggbb> <title>   (end card template, optional)
+//   outrologo <path>                    (logo on the end card, optional)
+//   posterframe <time>                  (optional)
+const MAGIC: &str = "videoedit-project 1";
+
+pub const POSTER_WIDTH: u32 = 160;
+pub const POSTER_HEIGHT: u32 = 90;
+
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Quick-look summary stored at the top of every project file.
+pub struct ProjectInfo {
+    pub duration_ms: u32,
+    pub clip_count: usize,
+    pub poster_png: Option<Vec<u8>>,
+}
+
+pub struct Project {
+    pub info: ProjectInfo,
+    pub media: Vec<MediaItem>,
+    pub clips: Vec<VideoClip>,
+    pub markers: Vec<Marker>,
+    pub audio_tracks: AudioTracks,
+    pub outro: OutroTemplate,
+    pub poster_frame: Option<u32>,
+}
+
+/// Writes the project; the duration and clip count in `project.info` are recomputed.
+pub fn save(path: &Path, project: &Project) -> Result<(), &'static str> {
+    let Project { info, media, clips, markers, audio_tracks, outro, poster_frame } = project;
+    let duration_ms = clips.iter().map(|c| c.timeline_start + (c.trim_end - c.trim_start)).fold(0, u32::max);
+
+    let mut out = String::new();
+    out.push_str(MAGIC);
+    out.push('\n');
+    out.push_str(&format!("info {} {}\n", duration_ms, clips.len()));
+    if let Some(png) = &info.poster_png {
+        out.push_str(&format!("poster {}\n", to_hex(png)));
+    }
+    for item in media {
+        out.push_str(&format!("media {} {}\n", item.duration, item.path.display()));
+        if let Some((size, modified)) = item.stamp {
+            out.push_str(&format!("mediastamp {} {}\n", size, modified));
+        }
+    }
+    for clip in clips {
+        // a clip running past a source that got shorter keeps a valid record; the media
+        // record holds the probed length, which is put back on load
+        out.push_str(&format!(
+            "clip {} {} {} {} {}\n",
+            clip.timeline_start, clip.trim_start, clip.trim_end, clip.duration.max(clip.trim_end), clip.path.display()
+        ));
+        if let Some(zoom) = clip.stabilize_zoom {
+            out.push_str(&format!("set stabilize {}\n", zoom));
+        }
+        if let Some(crop) = clip.crop {
+            out.push_str(&format!("set crop {} {} {} {}\n", crop.width, crop.height, crop.x, crop.y));
+        }
+        if clip.rotation != 0 {
+            out.push_str(&format!("set rotate {}\n", clip.rotation));
+        }
+        if clip.hflip {
+            out.push_str("set hflip 1\n");
+        }
+        if clip.vflip {
+            out.push_str("set vflip 1\n");
+        }
+        if let Some([r, g, b]) = clip.generator {
+            out.push_str(&format!("set generator {:02x}{:02x}{:02x}\n", r, g, b));
+        }
+        if clip.audio_offset != 0 {
+            out.push_str(&format!("set avoffset {}\n", clip.audio_offset));
+        }
+        if clip.stem != Stem::default() {
+            out.push_str(&format!("set stem {}\n", clip.stem.key()));
+        }
+        if clip.grade != ColorGrade::default() {
+            let [r, g, b] = clip.grade.gain;
+            let [ro, go, bo] = clip.grade.offset;
+            out.push_str(&format!("set grade {} {} {} {} {} {}\n", r, g, b, ro, go, bo));
+        }
+    }
+    for marker in markers {
+        out.push_str(&format!("marker {} {}\n", marker.time, marker.note.replace(['\r', '\n'], " ")));
+    }
+
+    if !audio_tracks.language.is_empty() {
+        out.push_str(&format!("audiolang {}\n", audio_tracks.language));
+    }
+    for alt in &audio_tracks.alternates {
+        let language = if alt.language.is_empty() { "-" } else { &alt.language };
+        out.push_str(&format!("altaudio {} {}\n", language, alt.path.display()));
+    }
+    if audio_tracks.cut_fade_frames > 0 {
+        out.push_str(&format!("cutfade {}\n", audio_tracks.cut_fade_frames));
+    }
+    if *outro != OutroTemplate::default() {
+        let [r, g, b] = outro.background;
+        out.push_str(&format!(
+            "outro {} {} {:02x}{:02x}{:02x} {}\n",
+            outro.duration_ms, outro.fade_ms, r, g, b, outro.title.replace(['\r', '\n'], " ")
+        ));
+        if let Some(logo) = &outro.logo {
+            out.push_str(&format!("outrologo {}\n", logo.display()));
+        }
+    }
+
+    if let Some(time) = poster_frame {
+        out.push_str(&format!("posterframe {}\n", time));
+    }
+
+    fs::write(path, out).map_err(|_| "Error writing project file")
+}
+
+pub fn load(path: &Path) -> Result<Project, &'static str> {
+    parse(path, false)
+}
+
+/// Reads only the summary records, stopping before the clip list.
+pub fn read_info(path: &Path) -> Result<ProjectInfo, &'static str> {
+    parse(path, true).map(|project| project.info)
+}
+
+fn parse(path: &Path, info_only: bool) -> Result<Project, &'static str> {
+    let text = fs::read_to_string(path).map_err(|_| "Error reading project file")?;
+    let mut lines = text.lines();
+    if lines.next() != Some(MAGIC) {
+        return Err("Not a project file");
+    }
+
+    let mut info = ProjectInfo { duration_ms: 0, clip_count: 0, poster_png: None };
+    let mut media = Vec::new();
+    let mut clips = Vec::new();
+    let mut markers = Vec::new();
+    let mut audio_tracks = AudioTracks::default();
+    let mut outro = OutroTemplate::default();
+    let mut poster_frame = None;
+
+    for line in lines {
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match kind {
+            "info" => {
+                let mut fields = rest.split(' ').map(|f| f.parse::<u32>().ok());
+                info.duration_ms = fields.next().flatten().ok_or("Malformed info record")?;
+                info.clip_count = fields.next().flatten().ok_or("Malformed info record")? as usize;
+            }
+            "poster" => info.poster_png = from_hex(rest),
+            "media" | "clip" if info_only => break,
+            "media" => {
+                let (duration, path) = rest.split_once(' ').ok_or("Malformed media record")?;
+                let path = PathBuf::from(path);
+                media.push(MediaItem {
+                    name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                    duration: duration.parse().map_err(|_| "Malformed media record")?,
+                    path,
+                    stamp: None,
+                });
+            }
+            "mediastamp" => {
+                let item = media.last_mut().ok_or("Media stamp without a media record")?;
+                let (size, modified) = rest.split_once(' ').ok_or("Malformed media stamp")?;
+                item.stamp = size.parse().ok().zip(modified.parse().ok());
+            }
+            "clip" => clips.push(parse_clip(rest)?),
+            "set" => {
+                let clip = clips.last_mut().ok_or("Clip option without a clip")?;
+                let (key, value) = rest.split_once(' ').unwrap_or((rest, ""));
+                match key {
+                    "stabilize" => clip.stabilize_zoom = value.parse().ok(),
+                    "crop" => {
+                        let values: Vec<u32> = value.split(' ').filter_map(|v| v.parse().ok()).collect();
+                        if let [width, height, x, y] = values[..] {
+                            clip.crop = Some(Crop { width, height, x, y });
+                        }
+                    }
+                    "rotate" => clip.rotation = value.parse::<u32>().map(|r| r / 90 % 4 * 90).unwrap_or(0),
+                    "avoffset" => clip.audio_offset = value.parse().unwrap_or(0),
+                    "stem" => clip.stem = Stem::ALL.into_iter().find(|s| s.key() == value).unwrap_or_default(),
+                    "hflip" => clip.hflip = value == "1",
+                    "vflip" => clip.vflip = value == "1",
+                    "generator" => {
+                        let rgb = u32::from_str_radix(value, 16).map_err(|_| "Malformed generator colour")?;
+                        clip.generator = Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]);
+                        clip.name = "Solid colour".to_string();
+                    }
+                    "grade" => {
+                        let values: Vec<f32> = value.split(' ').filter_map(|v| v.parse().ok()).collect();
+                        if let [r, g, b, ro, go, bo] = values[..] {
+                            clip.grade = ColorGrade { gain: [r, g, b], offset: [ro, go, bo] };
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "marker" => {
+                let (time, note) = rest.split_once(' ').unwrap_or((rest, ""));
+                let time = time.parse().map_err(|_| "Malformed marker record")?;
+                markers.push(Marker { time, note: note.to_string() });
+            }
+            "audiolang" => audio_tracks.language = rest.to_string(),
+            "altaudio" => {
+                let (language, path) = rest.split_once(' ').ok_or("Malformed alternate audio record")?;
+                audio_tracks.alternates.push(AltAudio {
+                    path: PathBuf::from(path),
+                    language: if language == "-" { String::new() } else { language.to_string() },
+                });
+            }
+            "cutfade" => audio_tracks.cut_fade_frames = rest.parse().unwrap_or(0),
+            "outro" => {
+                let mut fields = rest.splitn(4, ' ');
+                let mut next = || fields.next().ok_or("Malformed outro record");
+                outro.duration_ms = next()?.parse().map_err(|_| "Malformed outro record")?;
+                outro.fade_ms = next()?.parse().map_err(|_| "Malformed outro record")?;
+                let rgb = u32::from_str_radix(next()?, 16).map_err(|_| "Malformed outro colour")?;
+                outro.background = [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8];
+                outro.title = fields.next().unwrap_or("").to_string();
+            }
+            "outrologo" => outro.logo = Some(PathBuf::from(rest)),
+            "posterframe" => poster_frame = rest.parse().ok(),
+            _ => {} // unknown records are skipped so older builds can open newer files
+        }
+    }
+
+    for clip in clips.iter_mut().filter(|c| c.generator.is_none()) {
+        if let Some(item) = media.iter().find(|m| m.path == clip.path) {
+            clip.duration = item.duration;
+        }
+    }
+
+    Ok(Project { info, media, clips, markers, audio_tracks, outro, poster_frame })
+}
+
+fn parse_clip(rest: &str) -> Result<VideoClip, &'static str> {
+    let mut fields = rest.splitn(5, ' ');
+    let mut next = || fields.next().and_then(|f| f.parse::<u32>().ok()).ok_or("Malformed clip record");
+    let timeline_start = next()?;
+    let trim_start = next()?;
+    let trim_end = next()?;
+    let duration = next()?;
+    // every clip length is computed as trim_end - trim_start
+    if trim_end <= trim_start || trim_end > duration {
+        return Err("Malformed clip record");
+    }
+    let path = PathBuf::from(fields.next().ok_or("Malformed clip record")?);
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    Ok(VideoClip {
+        path,
+        name,
+        duration,
+        timeline_start,
+        trim_start,
+        trim_end,
+        ..Default::default()
+    })
+}
+
+/// Grabs a small PNG poster frame at `source_ms` into the given media file.
+pub fn capture_poster(path: &Path, source_ms: u32) -> Option<Vec<u8>> {
+    let output = Command::new("ffmpeg")
+        .arg("-ss").arg(format!("{:.3}", source_ms as f32 / 1000.0))
+        .arg("-i").arg(path)
+        .arg("-frames:v").arg("1")
+        .arg("-vf").arg(format!(
+            "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2",
+            POSTER_WIDTH, POSTER_HEIGHT, POSTER_WIDTH, POSTER_HEIGHT
+        ))
+        .arg("-f").arg("image2")
+        .arg("-c:v").arg("png")
+        .arg("-")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+}
+
+pub fn poster_to_image(png: &[u8]) -> Option<egui::ColorImage> {
+    let image = image::load_from_memory(png).ok()?.to_rgba8();
+    Some(egui::ColorImage::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        image.as_raw(),
+    ))
+}
+
+pub fn load_recent() -> Vec<PathBuf> {
+    settings::config_dir()
+        .and_then(|dir| fs::read_to_string(dir.join("recent.txt")).ok())
+        .map(|text| text.lines().filter(|l| !l.is_empty()).map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// Moves `path` to the front of the recent list and persists it.
+pub fn push_recent(path: &Path) -> Vec<PathBuf> {
+    let mut recent = load_recent();
+    recent.retain(|p| p != path);
+    recent.insert(0, path.to_path_buf());
+    recent.truncate(MAX_RECENT_PROJECTS);
+
+    if let Some(dir) = settings::config_dir() {
+        let text: String = recent.iter().map(|p| format!("{}\n", p.display())).collect();
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(dir.join("recent.txt"), text);
+    }
+    recent
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
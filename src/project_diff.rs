@@ -0,0 +1,77 @@
+use crate::VideoClip;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Change {
+    Unchanged,
+    Moved,     // same source range, different timeline position
+    Retrimmed, // same source, different in/out (possibly moved too)
+    Added,
+    Removed,
+}
+
+impl Change {
+    pub fn label(self) -> &'static str {
+        match self {
+            Change::Unchanged => "unchanged",
+            Change::Moved => "moved",
+            Change::Retrimmed => "re-trimmed",
+            Change::Added => "added",
+            Change::Removed => "removed",
+        }
+    }
+
+    pub fn color(self) -> egui::Color32 {
+        match self {
+            Change::Unchanged => egui::Color32::from_gray(110),
+            Change::Moved => egui::Color32::from_rgb(80, 140, 230),
+            Change::Retrimmed => egui::Color32::from_rgb(230, 170, 40),
+            Change::Added => egui::Color32::from_rgb(60, 180, 80),
+            Change::Removed => egui::Color32::from_rgb(210, 60, 60),
+        }
+    }
+}
+
+/// One row of the comparison; `old`/`new` index the saved and current clip lists.
+pub struct ClipDiff {
+    pub change: Change,
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+}
+
+/// Pairs each current clip with the saved clip from the same source whose used range overlaps
+/// it most (generators pair by colour, nearest first), then classifies every pair.
+pub fn diff(old: &[VideoClip], new: &[VideoClip]) -> Vec<ClipDiff> {
+    let mut taken = vec![false; old.len()];
+    let mut rows = Vec::new();
+    for (n, clip) in new.iter().enumerate() {
+        let best = old.iter().enumerate()
+            .filter(|(o, candidate)| !taken[*o] && candidate.path == clip.path && candidate.generator == clip.generator)
+            .map(|(o, candidate)| {
+                let overlap = candidate.trim_end.min(clip.trim_end).saturating_sub(candidate.trim_start.max(clip.trim_start));
+                (o, overlap, candidate.timeline_start.abs_diff(clip.timeline_start))
+            })
+            .filter(|(_, overlap, _)| *overlap > 0 || clip.generator.is_some())
+            .max_by_key(|(_, overlap, distance)| (*overlap, std::cmp::Reverse(*distance)));
+
+        let row = match best {
+            Some((o, _, _)) => {
+                taken[o] = true;
+                let before = &old[o];
+                let change = if (before.trim_start, before.trim_end) != (clip.trim_start, clip.trim_end) {
+                    Change::Retrimmed
+                } else if before.timeline_start != clip.timeline_start {
+                    Change::Moved
+                } else {
+                    Change::Unchanged
+                };
+                ClipDiff { change, old: Some(o), new: Some(n) }
+            }
+            None => ClipDiff { change: Change::Added, old: None, new: Some(n) },
+        };
+        rows.push(row);
+    }
+    for (o, _) in taken.iter().enumerate().filter(|(_, taken)| !**taken) {
+        rows.push(ClipDiff { change: Change::Removed, old: Some(o), new: None });
+    }
+    rows
+}
@@ -0,0 +1,111 @@
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+/// A machine reachable with `ssh <host>` (keys or agent, no password prompt) that has ffmpeg.
+#[derive(Clone, Default, PartialEq)]
+pub struct RemoteTarget {
+    pub host: String, // user@host or an ssh config alias
+    pub work_dir: String,
+}
+
+pub enum RemoteEvent {
+    Status(String),
+    Progress(f32), // 0..1 of the render
+    Finished(Result<(), String>),
+}
+
+/// Uploads the sources, runs the render over ssh streaming ffmpeg's progress back,
+/// and downloads the result to `output`. `args` are local export arguments whose
+/// source paths and final output path are rewritten for the remote side.
+pub fn start(
+    target: RemoteTarget,
+    mut args: Vec<String>,
+    sources: Vec<PathBuf>,
+    output: PathBuf,
+    duration_ms: u32,
+    ctx: egui::Context,
+) -> mpsc::Receiver<RemoteEvent> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let send = |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        };
+        let result = (|| {
+            let work_dir = target.work_dir.trim_end_matches('/');
+            ssh(&target.host, &format!("mkdir -p {}", quote(work_dir)))?;
+
+            for (i, source) in sources.iter().enumerate() {
+                let name = source.file_name().unwrap_or_default().to_string_lossy();
+                send(RemoteEvent::Status(format!("Uploading {} ({}/{}) ...", name, i + 1, sources.len())));
+                // numbered so same-named files from different folders do not collide
+                let remote_path = format!("{}/{}_{}", work_dir, i, name);
+                scp(&source.to_string_lossy(), &format!("{}:{}", target.host, remote_path))?;
+                let local = source.to_string_lossy();
+                for arg in args.iter_mut().filter(|a| **a == local) {
+                    *arg = remote_path.clone();
+                }
+            }
+
+            let remote_output = format!("{}/{}", work_dir, output.file_name().unwrap_or_default().to_string_lossy());
+            if let Some(last) = args.last_mut() {
+                *last = remote_output.clone();
+            }
+
+            send(RemoteEvent::Status(format!("Rendering on {} ...", target.host)));
+            let command = std::iter::once("ffmpeg -nostats -progress pipe:1".to_string())
+                .chain(args.iter().map(|a| quote(a)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let mut child = Command::new("ssh")
+                .args(["-o", "BatchMode=yes"])
+                .arg(&target.host)
+                .arg(command)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|_| "Error running ssh".to_string())?;
+            // -progress writes key=value blocks; out_time_us is the position in the output
+            for line in BufReader::new(child.stdout.take().unwrap()).lines().map_while(Result::ok) {
+                if let Some(us) = line.strip_prefix("out_time_us=").and_then(|v| v.parse::<u64>().ok()) {
+                    send(RemoteEvent::Progress((us as f32 / 1000.0 / duration_ms.max(1) as f32).min(1.0)));
+                }
+            }
+            if !child.wait().is_ok_and(|s| s.success()) {
+                return Err("Remote render failed".to_string());
+            }
+
+            send(RemoteEvent::Status("Downloading render ...".to_string()));
+            scp(&format!("{}:{}", target.host, remote_output), &output.to_string_lossy())
+        })();
+        send(RemoteEvent::Finished(result));
+    });
+    receiver
+}
+
+fn ssh(host: &str, command: &str) -> Result<(), String> {
+    let status = Command::new("ssh").args(["-o", "BatchMode=yes", host, command]).status();
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        _ => Err(format!("Cannot reach {} over ssh", host)),
+    }
+}
+
+// -s forces the SFTP protocol (the default only from OpenSSH 9.0), which does not pass remote
+// paths through a shell, so they are not quoted; clients too old to know -s fail the transfer
+// rather than mangle paths with spaces or shell characters
+fn scp(from: &str, to: &str) -> Result<(), String> {
+    let status = Command::new("scp").args(["-q", "-B", "-s", from, to]).status();
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        _ => Err(format!("Transfer failed: {}", Path::new(from).file_name().unwrap_or_default().to_string_lossy())),
+    }
+}
+
+/// Single-quotes an argument for the remote POSIX shell.
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{format_precise_timecode, Marker};
+
+// review files are CSV with a header row:
+//   timecode,comment
+//   00:01:02.500,"Cut earlier, the pause is too long"
+
+pub fn export_csv(path: &Path, markers: &[Marker]) -> Result<(), &'static str> {
+    let mut out = String::from("timecode,comment\n");
+    for marker in markers {
+        out.push_str(&format!("{},{}\n", format_precise_timecode(marker.time), quote_csv(&marker.note)));
+    }
+    fs::write(path, out).map_err(|_| "Error writing review file")
+}
+
+pub fn import_csv(path: &Path) -> Result<Vec<Marker>, &'static str> {
+    let text = fs::read_to_string(path).map_err(|_| "Error reading review file")?;
+    let mut markers = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (timecode, comment) = line.split_once(',').unwrap_or((line, ""));
+        let Some(time) = parse_timecode(timecode.trim()) else {
+            if i == 0 {
+                continue; // header row
+            }
+            return Err("Malformed timecode in review file");
+        };
+        markers.push(Marker { time, note: unquote_csv(comment.trim()) });
+    }
+
+    Ok(markers)
+}
+
+/// Accepts `HH:MM:SS(.mmm)`, `MM:SS(.mmm)` or plain seconds.
+pub fn parse_timecode(text: &str) -> Option<u32> {
+    let mut secs = 0.0;
+    for part in text.split(':') {
+        let value: f64 = part.parse().ok()?;
+        if value < 0.0 {
+            return None;
+        }
+        secs = secs * 60.0 + value;
+    }
+    Some((secs * 1000.0).round() as u32)
+}
+
+fn quote_csv(text: &str) -> String {
+    if text.contains([',', '"']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn unquote_csv(text: &str) -> String {
+    match text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => text.to_string(),
+    }
+}
+
+/// YouTube description chapters. `chapters` are (output ms, title) pairs in any order.
+/// Returns the text plus warnings for rules YouTube enforces.
+pub fn youtube_chapters(chapters: &[(u32, String)]) -> (String, Vec<&'static str>) {
+    let mut chapters = chapters.to_vec();
+    chapters.sort_by_key(|(time, _)| *time);
+    if chapters.first().is_none_or(|(time, _)| *time >= 1000) {
+        chapters.insert(0, (0, "Intro".to_string()));
+    }
+
+    let long = chapters.last().is_some_and(|(time, _)| *time >= 3600 * 1000);
+    let mut text = String::new();
+    for (i, (time, title)) in chapters.iter().enumerate() {
+        let secs = time / 1000;
+        let stamp = if long {
+            format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+        } else {
+            format!("{:02}:{:02}", secs / 60, secs % 60)
+        };
+        let title = if title.trim().is_empty() { format!("Chapter {}", i + 1) } else { title.trim().to_string() };
+        text.push_str(&format!("{} {}\n", stamp, title));
+    }
+
+    let mut warnings = Vec::new();
+    if chapters.len() < 3 {
+        warnings.push("YouTube needs at least 3 chapters.");
+    }
+    if chapters.windows(2).any(|w| w[1].0 - w[0].0 < 10_000) {
+        warnings.push("YouTube chapters must be at least 10 seconds long.");
+    }
+    (text, warnings)
+}
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::VideoClip;
+
+/// Containers a compositor render is picked up from, in order of preference.
+pub const RESULT_EXTENSIONS: &[&str] = &["mov", "mp4", "mkv", "avi"];
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_INTERMEDIATES: u32 = 999; // numbered intermediates per source name
+
+/// A clip segment handed to an external compositor (Blender, Natron, ...). The trimmed
+/// source is written losslessly as `<name>.mkv`; a render saved next to it as
+/// `<name>_comp.<ext>` replaces the clip once the file stops growing.
+pub struct RoundTrip {
+    pub source: PathBuf,
+    pub trim_start: u32,
+    pub trim_end: u32,
+    pub intermediate: PathBuf,
+    last_size: Option<u64>,
+    last_poll: Instant,
+}
+
+impl RoundTrip {
+    pub fn result_stem(&self) -> String {
+        format!("{}_comp", self.intermediate.file_stem().unwrap_or_default().to_string_lossy())
+    }
+
+    /// The finished render, once one exists and its size held steady between two polls.
+    pub fn poll(&mut self) -> Option<PathBuf> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+        let result = RESULT_EXTENSIONS.iter()
+            .map(|ext| self.intermediate.with_file_name(format!("{}.{}", self.result_stem(), ext)))
+            .find(|path| path.exists())?;
+        let size = std::fs::metadata(&result).ok()?.len();
+        let settled = size > 0 && self.last_size == Some(size);
+        self.last_size = Some(size);
+        settled.then_some(result)
+    }
+}
+
+/// Writes the intermediate on a worker; the round trip arrives once on the returned channel.
+pub fn start(ctx: &egui::Context, clip: VideoClip, dir: PathBuf) -> mpsc::Receiver<Result<RoundTrip, &'static str>> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let _ = sender.send(send(&clip, &dir));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+/// Writes the clip's trimmed source (FFV1 video, PCM audio) into `dir`. Clip effects are
+/// left out and stay on the clip, so they apply to the composited result too.
+fn send(clip: &VideoClip, dir: &Path) -> Result<RoundTrip, &'static str> {
+    std::fs::create_dir_all(dir).map_err(|_| "Cannot create the round-trip folder")?;
+    let stem = clip.path.file_stem().unwrap_or_default().to_string_lossy();
+    let intermediate = (1..=MAX_INTERMEDIATES)
+        .map(|n| dir.join(format!("{}_{}.mkv", stem, n)))
+        .find(|p| !p.exists())
+        .ok_or("Too many round-trip intermediates for this clip; clear out the round-trip folder")?;
+
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-ss").arg(format!("{:.3}", clip.trim_start as f32 / 1000.0))
+        .arg("-t").arg(format!("{:.3}", (clip.trim_end - clip.trim_start) as f32 / 1000.0))
+        .arg("-i").arg(&clip.path)
+        .arg("-map").arg("0:v:0")
+        .arg("-map").arg("0:a:0?")
+        .arg("-c:v").arg("ffv1")
+        .arg("-level").arg("3")
+        .arg("-c:a").arg("pcm_s24le")
+        .arg(&intermediate)
+        .status()
+        .map_err(|_| "Error running ffmpeg")?;
+    if !status.success() {
+        return Err("Error writing the round-trip intermediate");
+    }
+
+    Ok(RoundTrip {
+        source: clip.path.clone(),
+        trim_start: clip.trim_start,
+        trim_end: clip.trim_end,
+        intermediate,
+        last_size: None,
+        last_poll: Instant::now(),
+    })
+}
+
+/// Opens the intermediate in the configured compositor, or its folder when none is set.
+pub fn open_in(compositor: &str, intermediate: &Path) {
+    let compositor = compositor.trim();
+    if compositor.is_empty() {
+        if let Some(dir) = intermediate.parent() {
+            crate::notify::open_path(dir);
+        }
+        return;
+    }
+    let _ = Command::new(compositor).arg(intermediate).spawn();
+}
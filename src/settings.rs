@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::remote::RemoteTarget;
+
+// settings.txt in the config dir, one "key value" record per line:
+//   cache_dir <path>
+//   window <x> <y> <width> <height>   (main window, restored at startup)
+//   remote_host <ssh host>
+//   remote_dir <path on the remote machine>
+//   clip_label <template>             (timeline clip labels, e.g. "{stem} {in}-{out}")
+//   compositor <program>              (opens round-trip intermediates, e.g. blender)
+//   review_dir <path>                 (folder the timed preview render is written to)
+//   review_minutes <n>                (minutes between preview renders, 0 = off)
+//
+// Portable mode: when a `portable.txt` file sits next to the executable, config and cache
+// live in `videoedit-data/` beside it instead of the per-user locations.
+const PORTABLE_MARKER: &str = "portable.txt";
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Settings {
+    pub cache_dir: Option<PathBuf>, // None = platform default
+    pub window: Option<[f32; 4]>, // outer position + inner size, in points
+    pub remote: RemoteTarget,
+    pub clip_label: String, // empty = file name
+    pub compositor: String, // empty = open the folder
+    pub review_dir: Option<PathBuf>,
+    pub review_minutes: u32, // 0 = no timed preview renders
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(PathBuf::from)
+}
+
+pub fn portable_root() -> Option<PathBuf> {
+    let dir = exe_dir()?;
+    dir.join(PORTABLE_MARKER).exists().then(|| dir.join("videoedit-data"))
+}
+
+pub fn set_portable(enabled: bool) -> Result<(), &'static str> {
+    let marker = exe_dir().ok_or("Cannot locate the executable")?.join(PORTABLE_MARKER);
+    if enabled {
+        fs::write(&marker, "Delete this file to store settings and caches in the user profile again.\n")
+    } else {
+        fs::remove_file(&marker)
+    }
+    .map_err(|_| "Cannot change portable mode next to the executable")
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(root) = portable_root() {
+        return Some(root.join("config"));
+    }
+    let base = std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("videoedit"))
+}
+
+pub fn default_cache_dir() -> PathBuf {
+    if let Some(root) = portable_root() {
+        return root.join("cache");
+    }
+    std::env::var_os("LOCALAPPDATA")
+        .or_else(|| std::env::var_os("XDG_CACHE_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("videoedit")
+}
+
+/// The configured cache location, falling back to the platform default.
+pub fn cache_dir() -> PathBuf {
+    load().cache_dir.unwrap_or_else(default_cache_dir)
+}
+
+pub fn load() -> Settings {
+    let mut settings = Settings::default();
+    let Some(text) = config_dir().and_then(|dir| fs::read_to_string(dir.join("settings.txt")).ok()) else {
+        return settings;
+    };
+    for line in text.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "cache_dir" if !value.is_empty() => settings.cache_dir = Some(PathBuf::from(value)),
+            "remote_host" => settings.remote.host = value.to_string(),
+            "remote_dir" => settings.remote.work_dir = value.to_string(),
+            "clip_label" => settings.clip_label = value.to_string(),
+            "compositor" => settings.compositor = value.to_string(),
+            "review_dir" if !value.is_empty() => settings.review_dir = Some(PathBuf::from(value)),
+            "review_minutes" => settings.review_minutes = value.parse().unwrap_or(0),
+            "window" => {
+                let values: Vec<f32> = value.split(' ').filter_map(|v| v.parse().ok()).collect();
+                if let [x, y, width, height] = values[..] {
+                    settings.window = Some([x, y, width, height]);
+                }
+            }
+            _ => {}
+        }
+    }
+    settings
+}
+
+pub fn save(settings: &Settings) -> Result<(), &'static str> {
+    let dir = config_dir().ok_or("No config directory")?;
+    let mut out = String::new();
+    if let Some(cache_dir) = &settings.cache_dir {
+        out.push_str(&format!("cache_dir {}\n", cache_dir.display()));
+    }
+    if !settings.remote.host.is_empty() {
+        out.push_str(&format!("remote_host {}\nremote_dir {}\n", settings.remote.host, settings.remote.work_dir));
+    }
+    if !settings.clip_label.trim().is_empty() {
+        out.push_str(&format!("clip_label {}\n", settings.clip_label.trim()));
+    }
+    if !settings.compositor.trim().is_empty() {
+        out.push_str(&format!("compositor {}\n", settings.compositor.trim()));
+    }
+    if let Some(dir) = &settings.review_dir {
+        out.push_str(&format!("review_dir {}\nreview_minutes {}\n", dir.display(), settings.review_minutes));
+    }
+    if let Some([x, y, width, height]) = settings.window {
+        out.push_str(&format!("window {} {} {} {}\n", x, y, width, height));
+    }
+    fs::create_dir_all(&dir).map_err(|_| "Error creating config directory")?;
+    fs::write(dir.join("settings.txt"), out).map_err(|_| "Error writing settings")
+}
@@ -0,0 +1,119 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::{ColorGrade, VideoClip};
+
+const SAMPLE_WIDTH: usize = 64;
+const SAMPLE_HEIGHT: usize = 36;
+const SAMPLES_PER_CLIP: u32 = 3;
+
+/// Per-channel RGB histograms sampled from a few frames across the trimmed clip.
+pub struct Histograms {
+    bins: [[u64; 256]; 3],
+}
+
+impl Histograms {
+    fn mean_and_deviation(&self, channel: usize) -> (f32, f32) {
+        let bins = &self.bins[channel];
+        let count: u64 = bins.iter().sum();
+        if count == 0 {
+            return (0.0, 0.0);
+        }
+        let mean = bins.iter().enumerate().map(|(v, n)| v as f64 * *n as f64).sum::<f64>() / count as f64;
+        let variance = bins.iter().enumerate().map(|(v, n)| (v as f64 - mean).powi(2) * *n as f64).sum::<f64>() / count as f64;
+        (mean as f32, variance.sqrt() as f32)
+    }
+}
+
+/// A "Match to…" running in the background; the target clip is found again by its source
+/// range when the proposal arrives.
+pub struct ShotMatch {
+    pub reference_name: String,
+    pub target_path: PathBuf,
+    pub target_trim: (u32, u32),
+    receiver: mpsc::Receiver<Option<ColorGrade>>,
+}
+
+impl ShotMatch {
+    /// The proposed grade once sampling finished, None inside when it failed.
+    pub fn poll(&self) -> Option<Option<ColorGrade>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Samples both clips on a worker thread. The reference is measured as it looks now, grade
+/// included; the target without its grade, since the proposal replaces that outright.
+pub fn start(ctx: &egui::Context, reference: &VideoClip, target: &VideoClip) -> ShotMatch {
+    let (sender, receiver) = mpsc::channel();
+    let (reference_job, target_job) = (reference.clone(), target.clone());
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let proposal = match (
+            sample_histograms(&reference_job, &reference_job.preview_filters()),
+            sample_histograms(&target_job, &target_job.orientation_filters()),
+        ) {
+            (Some(reference), Some(target)) => Some(propose_grade(&reference, &target)),
+            _ => None,
+        };
+        let _ = sender.send(proposal);
+        ctx.request_repaint();
+    });
+    ShotMatch {
+        reference_name: reference.name.clone(),
+        target_path: target.path.clone(),
+        target_trim: (target.trim_start, target.trim_end),
+        receiver,
+    }
+}
+
+/// `filters` is a filter chain ending in a comma, applied before the frames are scaled down.
+fn sample_histograms(clip: &VideoClip, filters: &str) -> Option<Histograms> {
+    let mut histograms = Histograms { bins: [[0; 256]; 3] };
+    let clip_duration = clip.trim_end - clip.trim_start;
+
+    for i in 0..SAMPLES_PER_CLIP {
+        let source_ms = clip.trim_start + clip_duration * (2 * i + 1) / (2 * SAMPLES_PER_CLIP);
+        let mut child = Command::new("ffmpeg")
+            .arg("-ss").arg(format!("{:.3}", source_ms as f32 / 1000.0))
+            .arg("-i").arg(&clip.path)
+            .arg("-frames:v").arg("1")
+            .arg("-vf").arg(format!("{}scale={}:{}", filters, SAMPLE_WIDTH, SAMPLE_HEIGHT))
+            .arg("-pix_fmt").arg("rgb24")
+            .arg("-f").arg("rawvideo")
+            .arg("-")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        let mut buffer = vec![0u8; SAMPLE_WIDTH * SAMPLE_HEIGHT * 3];
+        let read = child.stdout.take()?.read_exact(&mut buffer);
+        let _ = child.wait();
+        if read.is_err() {
+            continue;
+        }
+        for pixel in buffer.chunks_exact(3) {
+            for (channel, value) in pixel.iter().enumerate() {
+                histograms.bins[channel][*value as usize] += 1;
+            }
+        }
+    }
+
+    histograms.bins[0].iter().any(|n| *n > 0).then_some(histograms)
+}
+
+/// Proposes a per-channel gain/offset that maps the target's distribution onto the reference's.
+fn propose_grade(reference: &Histograms, target: &Histograms) -> ColorGrade {
+    let mut grade = ColorGrade::default();
+    for channel in 0..3 {
+        let (ref_mean, ref_dev) = reference.mean_and_deviation(channel);
+        let (target_mean, target_dev) = target.mean_and_deviation(channel);
+        let gain = if target_dev > 1.0 { (ref_dev / target_dev).clamp(0.5, 2.0) } else { 1.0 };
+        grade.gain[channel] = gain;
+        grade.offset[channel] = (ref_mean - target_mean * gain).clamp(-64.0, 64.0);
+    }
+    grade
+}
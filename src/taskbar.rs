@@ -0,0 +1,37 @@
+pub const APP_TITLE: &str = "Video Editor";
+
+/// Desktop entry the Unity launcher API matches the window against on Linux.
+#[cfg(target_os = "linux")]
+const DESKTOP_ID: &str = "application://videoedit.desktop";
+
+/// Shows render progress where it can be seen while the window is minimized: as a
+/// percentage in the window title (taskbar button, dock tooltip) and, on Linux docks and
+/// panels that implement the Unity launcher API (KDE, Dash to Dock, Plank), as a progress bar
+/// on the icon. `None` clears it.
+pub fn set_progress(ctx: &egui::Context, percent: Option<u32>) {
+    let title = match percent {
+        Some(percent) => format!("{}% · {}", percent, APP_TITLE),
+        None => APP_TITLE.to_string(),
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::{Command, Stdio};
+
+        let properties = match percent {
+            Some(percent) => format!("{{'progress': <{:.2}>, 'progress-visible': <true>}}", percent as f32 / 100.0),
+            None => "{'progress-visible': <false>}".to_string(),
+        };
+        // waited on a thread so the emitter does not linger as a zombie
+        std::thread::spawn(move || {
+            let _ = Command::new("gdbus")
+                .args(["emit", "--session", "--object-path", "/", "--signal", "com.canonical.Unity.LauncherEntry.Update"])
+                .arg(DESKTOP_ID)
+                .arg(properties)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        });
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::card::{self, CardClip};
+use crate::hash;
+
+pub enum TransferEvent {
+    Progress(String, f32), // status, 0..1 of the files
+    Finished {
+        done: Vec<PathBuf>,
+        failed: usize,
+        error: Option<&'static str>, // the first failure
+    },
+}
+
+/// Copies card clips into `media_dir` on a worker, file by file. Stops at the first failure;
+/// `done` lists the copies made before it.
+pub fn import_card(ctx: &egui::Context, clips: Vec<CardClip>, media_dir: PathBuf) -> mpsc::Receiver<TransferEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let send = |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        };
+        let mut done = Vec::new();
+        let mut error = None;
+        for (i, clip) in clips.iter().enumerate() {
+            let name = clip.path.file_name().unwrap_or_default().to_string_lossy();
+            send(TransferEvent::Progress(format!("Copying {} ({}/{}) ...", name, i + 1, clips.len()), i as f32 / clips.len() as f32));
+            match card::copy_to_media(clip, &media_dir) {
+                Ok(path) => done.push(path),
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+        send(TransferEvent::Finished { failed: clips.len() - done.len(), done, error });
+    });
+    receiver
+}
+
+/// Moves files into `dir` on a worker. Renames fail across drives, so those fall back to a
+/// verified copy and delete. Keeps going past failures; `done` lists the sources moved.
+pub fn move_media(ctx: &egui::Context, paths: Vec<PathBuf>, dir: PathBuf) -> mpsc::Receiver<TransferEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let send = |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        };
+        let mut done = Vec::new();
+        let mut error = None;
+        for (i, path) in paths.iter().enumerate() {
+            let name = path.file_name().unwrap_or_default();
+            send(TransferEvent::Progress(format!("Moving {} ({}/{}) ...", name.to_string_lossy(), i + 1, paths.len()), i as f32 / paths.len() as f32));
+            let target = dir.join(name);
+            let moved = std::fs::rename(path, &target).or_else(|_| {
+                let sha256 = hash::verified_copy(path, &target)?;
+                hash::record_checksum(&target, &sha256).map_err(|_| "Copied, but writing the checksum ledger failed")?;
+                std::fs::remove_file(path).map_err(|_| "Copied, but the original could not be deleted")
+            });
+            match moved {
+                Ok(()) => done.push(path.clone()),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        send(TransferEvent::Finished { failed: paths.len() - done.len(), done, error });
+    });
+    receiver
+}